@@ -1,52 +1,312 @@
-use crate::bevy::{from_meters, SIZE_SCALING_FACTOR, Z_LEVEL_ARROWS};
-use bevy::{app::Plugin, color::palettes::css::*, ecs::system::Commands, prelude::*};
+use crate::bevy::{
+    arrow_kind::{ArrowKind, HeadStyle, LineStyle},
+    camera::ZoomState,
+    history::{Edit, EditHistory},
+    input::{GlobalAction, InputMode},
+    playback::playback_inactive,
+    selection::{select_grabbed, DeleteSelected, Selectable, Selected, SelectionRegistry},
+    unit::Unit,
+    SIZE_SCALING_FACTOR, Z_LEVEL_ARROWS,
+};
+use bevy::{
+    app::Plugin, color::palettes::css::*, ecs::system::Commands, picking::backend::prelude::*,
+    prelude::*,
+};
 use bevy_prototype_lyon::prelude::*;
 use core::f32;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct ArrowPlugin;
 impl Plugin for ArrowPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_systems(Startup, sys_spawn_test_arrows)
-            .add_systems(Update, sys_update_arrow_visuals);
+        app.add_systems(PreUpdate, sys_pick_arrows.in_set(PickSet::Backend))
+            .add_systems(
+                Update,
+                sys_follow_anchors
+                    .before(sys_update_arrow_visuals)
+                    // arrows show the planned movement, even while units are played back
+                    .run_if(playback_inactive),
+            )
+            .add_systems(Update, sys_detach_removed_anchors)
+            .add_systems(Update, sys_update_arrow_visuals)
+            .add_systems(Update, sys_update_arrow_selection_visuals)
+            .add_systems(Update, sys_update_arrow_preview_visuals)
+            .add_systems(
+                Update,
+                sys_delete_selected_arrow.run_if(on_event::<DeleteSelected>),
+            )
+            .add_systems(
+                Update,
+                sys_on_input_mode_change.run_if(state_changed::<InputMode>.or(arrows_added)),
+            );
     }
 }
 
-fn sys_spawn_test_arrows(mut commands: Commands) {
-    spawn_arrow(
-        Arrow::Straight {
-            from: from_meters(5., 5.),
-            to: from_meters(7., 7.),
-        },
-        &mut commands,
-    );
-    spawn_arrow(
-        Arrow::Bezier {
-            from: from_meters(-5., -5.),
-            to: from_meters(-7., -7.),
-            control_from: from_meters(-7., -5.),
-            control_to: from_meters(-5., -7.),
-        },
-        &mut commands,
-    );
+const ARROW_COLOR: Srgba = BLACK;
+const SELECTED_ARROW_COLOR: Srgba = DARK_ORANGE;
+const ARROW_PREVIEW_COLOR: Srgba = Srgba {
+    alpha: 0.5,
+    ..ARROW_COLOR
+};
+/// How far away from an arrow's stroke a pointer may be to still hit it, in screen pixels.
+const ARROW_HIT_TOLERANCE: f32 = 15.;
+
+type ArrowVisualsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Arrow,
+        &'static ArrowKind,
+        &'static mut Path,
+        &'static mut Transform,
+        &'static mut Stroke,
+    ),
+    Or<(Changed<Arrow>, Changed<ArrowKind>)>,
+>;
+
+fn sys_update_arrow_visuals(mut q_arrows: ArrowVisualsQuery) {
+    for (arrow, kind, mut path, mut transform, mut stroke) in q_arrows.iter_mut() {
+        *path = calc_arrow_path(arrow, *kind);
+        *transform = arrow.get_transform();
+        stroke.options = kind.stroke_options();
+    }
+}
+
+fn sys_update_arrow_selection_visuals(
+    mut q_arrow: Query<&mut Stroke, With<Arrow>>,
+    q_selected: Query<Entity, With<Selected>>,
+    mut q_deselected: RemovedComponents<Selected>,
+) {
+    q_deselected.read().for_each(|entity| {
+        if let Ok(mut stroke) = q_arrow.get_mut(entity) {
+            stroke.color = ARROW_COLOR.into();
+        }
+    });
+
+    q_selected.iter().for_each(|entity| {
+        if let Ok(mut stroke) = q_arrow.get_mut(entity) {
+            stroke.color = SELECTED_ARROW_COLOR.into();
+        }
+    });
 }
 
-fn sys_update_arrow_visuals(
-    mut q_arrows: Query<(&Arrow, &mut Path, &mut Transform), Changed<Arrow>>,
+pub fn spawn_arrow(
+    arrow: Arrow,
+    kind: ArrowKind,
+    anchors: ArrowAnchors,
+    commands: &mut Commands,
+) -> Entity {
+    commands
+        .spawn((
+            arrow,
+            kind,
+            anchors,
+            ShapeBundle::default(),
+            Stroke {
+                color: ARROW_COLOR.into(),
+                options: kind.stroke_options(),
+            },
+            Selectable,
+        ))
+        .id()
+}
+
+/// An arrow which is still being drawn, shown on the board but not part of it yet.
+#[derive(Component, Clone, Debug)]
+pub struct ArrowPreview(pub Arrow, pub ArrowKind);
+
+pub fn spawn_arrow_preview(arrow: Arrow, kind: ArrowKind, commands: &mut Commands) -> Entity {
+    commands
+        .spawn((
+            ArrowPreview(arrow, kind),
+            ShapeBundle::default(),
+            Stroke {
+                color: ARROW_PREVIEW_COLOR.into(),
+                options: kind.stroke_options(),
+            },
+        ))
+        .id()
+}
+
+fn sys_update_arrow_preview_visuals(
+    mut q_previews: Query<(&ArrowPreview, &mut Path, &mut Transform), Changed<ArrowPreview>>,
 ) {
-    for (arrow, mut path, mut transform) in q_arrows.iter_mut() {
-        *path = calc_arrow_path(arrow);
+    for (ArrowPreview(arrow, kind), mut path, mut transform) in q_previews.iter_mut() {
+        *path = calc_arrow_path(arrow, *kind);
         *transform = arrow.get_transform();
     }
 }
 
-pub fn spawn_arrow(arrow: Arrow, commands: &mut Commands) {
-    commands.spawn((arrow, ShapeBundle::default(), Stroke::new(BLACK, 10.)));
+/// Picking backend hitting arrows anywhere close to their stroke, since the stroke itself is too
+/// thin to be hit reliably, especially by touch input.
+fn sys_pick_arrows(
+    r_ray_map: Res<RayMap>,
+    q_arrows: Query<(Entity, &Arrow)>,
+    q_cameras: Query<&Camera>,
+    r_zoom_state: Res<ZoomState>,
+    mut ew_pointer_hits: EventWriter<PointerHits>,
+) {
+    let tolerance = ARROW_HIT_TOLERANCE * r_zoom_state.current_zoom_factor;
+    for (ray_id, ray) in r_ray_map.iter() {
+        let Ok(camera) = q_cameras.get(ray_id.camera) else {
+            continue;
+        };
+        // the view is orthographic and looks straight down, so the ray hits where it starts
+        let position = ray.origin.xy();
+        let picks = q_arrows
+            .iter()
+            .filter(|(_, arrow)| arrow.is_near(position, tolerance))
+            .map(|(entity, _)| {
+                let hit = HitData::new(
+                    ray_id.camera,
+                    ray.origin.z - Z_LEVEL_ARROWS,
+                    Some(position.extend(Z_LEVEL_ARROWS)),
+                    None,
+                );
+                (entity, hit)
+            })
+            .collect::<Vec<_>>();
+        if !picks.is_empty() {
+            ew_pointer_hits.send(PointerHits::new(ray_id.pointer, picks, camera.order as f32));
+        }
+    }
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+fn sys_delete_selected_arrow(
+    mut er_delete_selected: EventReader<DeleteSelected>,
+    r_selection_registry: Res<SelectionRegistry>,
+    q_arrows: Query<(&Arrow, &ArrowKind, &ArrowAnchors)>,
+    mut r_history: ResMut<EditHistory>,
+    mut commands: Commands,
+) {
+    er_delete_selected.clear();
+    let mut deletions = Vec::new();
+    for selected in r_selection_registry.selected() {
+        if let Ok((arrow, kind, anchors)) = q_arrows.get(selected) {
+            commands.entity(selected).despawn();
+            deletions.push(Edit::DeleteArrow {
+                arrow_entity: selected,
+                arrow: arrow.clone(),
+                kind: *kind,
+                anchors: *anchors,
+            });
+        }
+    }
+    if let Some(edit) = Edit::group(deletions) {
+        r_history.record(edit);
+    }
+}
+
+/// Units the ends of an arrow are attached to, so they follow when the units are moved.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArrowAnchors {
+    pub from: Option<Entity>,
+    pub to: Option<Entity>,
+}
+impl ArrowAnchors {
+    pub fn remap(&mut self, old: Entity, new: Entity) {
+        for anchor in [&mut self.from, &mut self.to].into_iter().flatten() {
+            if *anchor == old {
+                *anchor = new;
+            }
+        }
+    }
+}
+
+fn sys_follow_anchors(
+    mut q_arrows: Query<(&mut Arrow, &ArrowAnchors)>,
+    q_moved_units: Query<&Transform, (With<Unit>, Changed<Transform>)>,
+) {
+    let moved_position = |anchor: Option<Entity>| {
+        anchor
+            .and_then(|unit| q_moved_units.get(unit).ok())
+            .map(|transform| transform.translation.xy())
+    };
+    for (mut arrow, anchors) in q_arrows.iter_mut() {
+        let (moved_from, moved_to) = (moved_position(anchors.from), moved_position(anchors.to));
+        if moved_from.is_none() && moved_to.is_none() {
+            continue;
+        }
+        let mut anchored_arrow = arrow.clone();
+        if let Some(position) = moved_from {
+            anchored_arrow = anchored_arrow.with_start(position);
+        }
+        if let Some(position) = moved_to {
+            anchored_arrow = anchored_arrow.with_end(position);
+        }
+        // avoid triggering change detection, which would re-tessellate the arrow needlessly
+        arrow.set_if_neq(anchored_arrow);
+    }
+}
+
+/// Keeps arrows of removed units where they are, but no longer attached to anything.
+fn sys_detach_removed_anchors(
+    mut q_removed_units: RemovedComponents<Unit>,
+    mut q_anchors: Query<&mut ArrowAnchors>,
+) {
+    for unit in q_removed_units.read() {
+        for mut anchors in q_anchors.iter_mut() {
+            if anchors.from == Some(unit) {
+                anchors.from = None;
+            }
+            if anchors.to == Some(unit) {
+                anchors.to = None;
+            }
+        }
+    }
+}
+
+fn arrows_added(q_added: Query<(), Added<Arrow>>) -> bool {
+    !q_added.is_empty()
+}
+
+/// Marks the observers handling pointer input on arrows, so they can be replaced when the set of
+/// arrows changes.
+#[derive(Component)]
+struct ArrowInputObserver;
+
+fn sys_on_input_mode_change(
+    current_input_mode: Res<State<InputMode>>,
+    q_arrows: Query<Entity, With<Arrow>>,
+    q_observers: Query<Entity, With<ArrowInputObserver>>,
+    mut commands: Commands,
+) {
+    q_observers.iter().for_each(|observer| {
+        commands.entity(observer).despawn();
+    });
+
+    let input_observers = match **current_input_mode {
+        InputMode::View => None,
+        InputMode::Position | InputMode::Movement => {
+            Some(vec![Observer::new(on_arrow_grabbed_do_select)])
+        }
+    };
+    if let Some(observers) = input_observers {
+        observers.into_iter().for_each(|mut observer| {
+            q_arrows
+                .iter()
+                .for_each(|arrow| observer.watch_entity(arrow));
+            commands.spawn((
+                observer,
+                ArrowInputObserver,
+                StateScoped(**current_input_mode),
+            ));
+        });
+    }
+}
+
+fn on_arrow_grabbed_do_select(
+    trigger: Trigger<Pointer<Down>>,
+    action_state: Res<ActionState<GlobalAction>>,
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+) {
+    select_grabbed(trigger.target, &action_state, &mut r_selection_registry);
+}
+
+/// The path of an arrow, see [`ArrowAnchors`] for arrows attached to units.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Arrow {
-    // TODO from and to should take a Vec2 or a Unit
-    // add new type LocalizedArrow to handle this change for calculations
     Straight {
         from: Vec2,
         to: Vec2,
@@ -57,13 +317,37 @@ pub enum Arrow {
         control_from: Vec2,
         control_to: Vec2,
     },
+    /// A curve sketched by hand, passing smoothly through all of its points.
+    Freehand {
+        points: Vec<Vec2>,
+    },
 }
 impl Arrow {
-    fn localized(&self) -> Arrow {
+    /// Turns the path traced by a drag into an arrow, dropping the jitter of the drawing hand.
+    ///
+    /// Paths which are (almost) straight become straight arrows, paths too short to make out an
+    /// arrow are discarded, e.g. when the drag returns to where it started.
+    pub fn from_sketch(sketch: &[Vec2]) -> Option<Arrow> {
+        let points = simplify_path(sketch, SKETCH_TOLERANCE);
+        let length: f32 = points
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum();
+        if length < MIN_SKETCH_LENGTH {
+            return None;
+        }
+        if let [from, to] = points[..] {
+            return Some(Arrow::Straight { from, to });
+        }
+        (points.len() > 2).then_some(Arrow::Freehand { points })
+    }
+
+    /// Applies `f` to every point of the arrow, e.g. to convert between pixels and meters.
+    pub fn map_points(&self, f: impl Fn(Vec2) -> Vec2) -> Arrow {
         match self {
             Arrow::Straight { from, to } => Arrow::Straight {
-                from: Vec2::ZERO,
-                to: to - from,
+                from: f(*from),
+                to: f(*to),
             },
             Arrow::Bezier {
                 from,
@@ -71,29 +355,307 @@ impl Arrow {
                 control_from,
                 control_to,
             } => Arrow::Bezier {
-                from: Vec2::ZERO,
-                to: to - from,
-                control_from: control_from - from,
-                control_to: control_to - from,
+                from: f(*from),
+                to: f(*to),
+                control_from: f(*control_from),
+                control_to: f(*control_to),
+            },
+            Arrow::Freehand { points } => Arrow::Freehand {
+                points: points.iter().copied().map(f).collect(),
             },
         }
     }
 
-    fn get_transform(&self) -> Transform {
-        let from = match self {
-            Arrow::Straight { from, to: _ } => from,
+    /// Approximates the arrow's path, without the arrowhead, by a polyline.
+    pub fn polyline(&self) -> Vec<Vec2> {
+        match self {
+            Arrow::Straight { from, to } => vec![*from, *to],
             Arrow::Bezier {
                 from,
-                to: _,
-                control_from: _,
-                control_to: _,
-            } => from,
-        };
+                to,
+                control_from,
+                control_to,
+            } => sample_bezier(*from, *control_from, *control_to, *to, BEZIER_SEGMENTS),
+            Arrow::Freehand { points } => {
+                let mut polyline = vec![self.start()];
+                smooth_path(points)
+                    .iter()
+                    .for_each(|[from, control_from, control_to, to]| {
+                        let samples = sample_bezier(
+                            *from,
+                            *control_from,
+                            *control_to,
+                            *to,
+                            FREEHAND_SEGMENTS,
+                        );
+                        polyline.extend(samples.into_iter().skip(1));
+                    });
+                polyline
+            }
+        }
+    }
+
+    pub fn is_near(&self, point: Vec2, tolerance: f32) -> bool {
+        self.polyline()
+            .windows(2)
+            .any(|segment| distance_to_segment(point, segment[0], segment[1]) <= tolerance)
+    }
+
+    pub fn start(&self) -> Vec2 {
+        match self {
+            Arrow::Straight { from, .. } | Arrow::Bezier { from, .. } => *from,
+            Arrow::Freehand { points } => points.first().copied().unwrap_or_default(),
+        }
+    }
+
+    pub fn end(&self) -> Vec2 {
+        match self {
+            Arrow::Straight { to, .. } | Arrow::Bezier { to, .. } => *to,
+            Arrow::Freehand { points } => points.last().copied().unwrap_or_default(),
+        }
+    }
+
+    /// Moves the start of the arrow, along with its control point to keep the shape of the curve.
+    pub fn with_start(&self, position: Vec2) -> Arrow {
+        let delta = position - self.start();
+        match self {
+            Arrow::Straight { to, .. } => Arrow::Straight {
+                from: position,
+                to: *to,
+            },
+            Arrow::Bezier {
+                to,
+                control_from,
+                control_to,
+                ..
+            } => Arrow::Bezier {
+                from: position,
+                to: *to,
+                control_from: *control_from + delta,
+                control_to: *control_to,
+            },
+            Arrow::Freehand { points } => {
+                let mut points = points.clone();
+                if let Some(first) = points.first_mut() {
+                    *first = position;
+                }
+                Arrow::Freehand { points }
+            }
+        }
+    }
+
+    /// Moves the end of the arrow, along with its control point to keep the shape of the curve.
+    pub fn with_end(&self, position: Vec2) -> Arrow {
+        let delta = position - self.end();
+        match self {
+            Arrow::Straight { from, .. } => Arrow::Straight {
+                from: *from,
+                to: position,
+            },
+            Arrow::Bezier {
+                from,
+                control_from,
+                control_to,
+                ..
+            } => Arrow::Bezier {
+                from: *from,
+                to: position,
+                control_from: *control_from,
+                control_to: *control_to + delta,
+            },
+            Arrow::Freehand { points } => {
+                let mut points = points.clone();
+                if let Some(last) = points.last_mut() {
+                    *last = position;
+                }
+                Arrow::Freehand { points }
+            }
+        }
+    }
+
+    /// The point halfway along the arrow's path.
+    pub fn midpoint(&self) -> Vec2 {
+        match self {
+            Arrow::Straight { from, to } => from.lerp(*to, 0.5),
+            Arrow::Bezier {
+                from,
+                to,
+                control_from,
+                control_to,
+            } => cubic_bezier_point(*from, *control_from, *control_to, *to, 0.5),
+            Arrow::Freehand { .. } => {
+                let polyline = self.polyline();
+                let fractions = length_fractions(&polyline);
+                match fractions.iter().position(|fraction| *fraction >= 0.5) {
+                    Some(index) if index > 0 => {
+                        let (before, after) = (fractions[index - 1], fractions[index]);
+                        polyline[index - 1].lerp(polyline[index], (0.5 - before) / (after - before))
+                    }
+                    _ => self.start(),
+                }
+            }
+        }
+    }
 
+    /// Bends the arrow so its midpoint moves by `delta`, turning straight arrows into curves.
+    pub fn with_midpoint_moved(&self, delta: Vec2) -> Arrow {
+        // moving both control points by d moves the midpoint by 3/4 d
+        let control_delta = delta * 4. / 3.;
+        match self {
+            Arrow::Straight { from, to } => Arrow::Bezier {
+                from: *from,
+                to: *to,
+                // control points on the line itself describe the same straight path
+                control_from: from.lerp(*to, 1. / 3.) + control_delta,
+                control_to: from.lerp(*to, 2. / 3.) + control_delta,
+            },
+            Arrow::Bezier {
+                from,
+                to,
+                control_from,
+                control_to,
+            } => Arrow::Bezier {
+                from: *from,
+                to: *to,
+                control_from: *control_from + control_delta,
+                control_to: *control_to + control_delta,
+            },
+            Arrow::Freehand { points } => {
+                // the ends stay in place, the points in between follow the more the closer they
+                // are to the middle, measured along the curve just like the midpoint itself
+                let fractions = length_fractions(&self.polyline());
+                Arrow::Freehand {
+                    points: points
+                        .iter()
+                        .enumerate()
+                        .map(|(index, point)| {
+                            // the curve passes through every point, see `polyline`
+                            let fraction = fractions[index * FREEHAND_SEGMENTS];
+                            *point + delta * (fraction * f32::consts::PI).sin()
+                        })
+                        .collect(),
+                }
+            }
+        }
+    }
+
+    fn localized(&self) -> Arrow {
+        let from = self.start();
+        self.map_points(|point| point - from)
+    }
+
+    fn get_transform(&self) -> Transform {
+        let from = self.start();
         Transform::from_xyz(from.x, from.y, Z_LEVEL_ARROWS)
     }
 }
 
+const BEZIER_SEGMENTS: usize = 16;
+const FREEHAND_SEGMENTS: usize = 8; // per point of the freehand arrow
+fn cubic_bezier_point(from: Vec2, control_from: Vec2, control_to: Vec2, to: Vec2, t: f32) -> Vec2 {
+    let u = 1. - t;
+    from * u * u * u + control_from * 3. * u * u * t + control_to * 3. * u * t * t + to * t * t * t
+}
+
+fn sample_bezier(
+    from: Vec2,
+    control_from: Vec2,
+    control_to: Vec2,
+    to: Vec2,
+    segments: usize,
+) -> Vec<Vec2> {
+    (0..=segments)
+        .map(|segment| {
+            let t = segment as f32 / segments as f32;
+            cubic_bezier_point(from, control_from, control_to, to, t)
+        })
+        .collect()
+}
+
+/// How far along the polyline each of its points lies, as a fraction of its whole length.
+///
+/// All fractions are 0 if the polyline has no length.
+fn length_fractions(polyline: &[Vec2]) -> Vec<f32> {
+    let mut travelled = 0.;
+    let mut lengths = Vec::with_capacity(polyline.len());
+    for (index, point) in polyline.iter().enumerate() {
+        if index > 0 {
+            travelled += polyline[index - 1].distance(*point);
+        }
+        lengths.push(travelled);
+    }
+    if travelled > 0. {
+        lengths.iter_mut().for_each(|length| *length /= travelled);
+    }
+    lengths
+}
+
+/// Connects the points by cubic bezier curves `[from, control_from, control_to, to]`, forming a
+/// smooth (Catmull-Rom) curve through all of them.
+fn smooth_path(points: &[Vec2]) -> Vec<[Vec2; 4]> {
+    (1..points.len())
+        .map(|index| {
+            let before = points[index.saturating_sub(2)];
+            let from = points[index - 1];
+            let to = points[index];
+            let after = points[(index + 1).min(points.len() - 1)];
+            [
+                from,
+                from + (to - before) / 6.,
+                to - (after - from) / 6.,
+                to,
+            ]
+        })
+        .collect()
+}
+
+/// How far a sketched path may deviate from its simplified version.
+const SKETCH_TOLERANCE: f32 = 0.2 * SIZE_SCALING_FACTOR;
+/// How long a simplified sketch has to be to become an arrow.
+const MIN_SKETCH_LENGTH: f32 = 0.5 * SIZE_SCALING_FACTOR;
+
+/// Reduces the path to the points needed to stay within `tolerance` of it (Ramer-Douglas-Peucker).
+fn simplify_path(points: &[Vec2], tolerance: f32) -> Vec<Vec2> {
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+
+    let (farthest_index, farthest_distance) = points
+        .iter()
+        .enumerate()
+        .take(points.len() - 1)
+        .skip(1)
+        .map(|(index, point)| (index, distance_to_segment(*point, first, last)))
+        .fold((0, 0.), |farthest, candidate| {
+            if candidate.1 > farthest.1 {
+                candidate
+            } else {
+                farthest
+            }
+        });
+    if farthest_distance <= tolerance {
+        return vec![first, last];
+    }
+
+    let mut simplified = simplify_path(&points[..=farthest_index], tolerance);
+    simplified.pop(); // the farthest point starts the second half as well
+    simplified.extend(simplify_path(&points[farthest_index..], tolerance));
+    simplified
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let segment = end - start;
+    let t = if segment.length_squared() > 0. {
+        ((point - start).dot(segment) / segment.length_squared()).clamp(0., 1.)
+    } else {
+        0.
+    };
+    point.distance(start + segment * t)
+}
+
 struct Arrowhead {
     left: Vec2,
     right: Vec2,
@@ -110,6 +672,10 @@ fn calc_arrowhead(arrow: &Arrow) -> Option<Arrowhead> {
             control_from: _,
             control_to,
         } => (*control_to, *to),
+        Arrow::Freehand { points } => {
+            let [_, _, control_to, to] = *smooth_path(points).last()?;
+            (control_to, to)
+        }
     };
 
     (from - to).try_normalize().map(|direction| {
@@ -123,31 +689,255 @@ fn calc_arrowhead(arrow: &Arrow) -> Option<Arrowhead> {
     })
 }
 
-fn calc_arrow_path(arrow: &Arrow) -> Path {
+const DASH_LENGTH: f32 = 30.;
+const DASH_GAP: f32 = 20.;
+const DOT_LENGTH: f32 = 1.; // drawn as dots by the round line caps
+const DOT_GAP: f32 = 20.;
+const DOUBLE_LINE_OFFSET: f32 = 6.;
+
+fn calc_arrow_path(arrow: &Arrow, kind: ArrowKind) -> Path {
     let arrow = arrow.localized();
     let mut arrow_builder = PathBuilder::new();
-    match arrow {
-        Arrow::Straight { from, to } => {
-            arrow_builder.move_to(from);
-            arrow_builder.line_to(to);
+    match kind.line_style() {
+        LineStyle::Solid => match &arrow {
+            Arrow::Straight { from, to } => {
+                arrow_builder.move_to(*from);
+                arrow_builder.line_to(*to);
+            }
+            Arrow::Bezier {
+                from,
+                to,
+                control_from,
+                control_to,
+            } => {
+                arrow_builder.move_to(*from);
+                arrow_builder.cubic_bezier_to(*control_from, *control_to, *to);
+            }
+            Arrow::Freehand { points } => {
+                arrow_builder.move_to(arrow.start());
+                smooth_path(points)
+                    .iter()
+                    .for_each(|[_, control_from, control_to, to]| {
+                        arrow_builder.cubic_bezier_to(*control_from, *control_to, *to);
+                    });
+            }
+        },
+        LineStyle::Dashed => {
+            add_dashes(&mut arrow_builder, &arrow.polyline(), DASH_LENGTH, DASH_GAP)
         }
-        Arrow::Bezier {
-            from,
-            to,
-            control_from,
-            control_to,
-        } => {
-            arrow_builder.move_to(from);
-            arrow_builder.cubic_bezier_to(control_from, control_to, to);
+        LineStyle::Dotted => add_dashes(&mut arrow_builder, &arrow.polyline(), DOT_LENGTH, DOT_GAP),
+        LineStyle::Double => {
+            let polyline = arrow.polyline();
+            for offset in [DOUBLE_LINE_OFFSET, -DOUBLE_LINE_OFFSET] {
+                add_polyline(&mut arrow_builder, &offset_polyline(&polyline, offset));
+            }
         }
     }
     if let Some(arrow_head) = calc_arrowhead(&arrow) {
-        arrow_builder.move_to(arrow_head.point);
-        arrow_builder.line_to(arrow_head.right);
-        arrow_builder.move_to(arrow_head.point);
-        arrow_builder.line_to(arrow_head.left);
+        match kind.head_style() {
+            HeadStyle::Chevron => {
+                arrow_builder.move_to(arrow_head.point);
+                arrow_builder.line_to(arrow_head.right);
+                arrow_builder.move_to(arrow_head.point);
+                arrow_builder.line_to(arrow_head.left);
+            }
+            HeadStyle::Bar => {
+                let half_bar = (arrow_head.left - arrow_head.right) / 2.;
+                arrow_builder.move_to(arrow_head.point + half_bar);
+                arrow_builder.line_to(arrow_head.point - half_bar);
+            }
+            HeadStyle::Triangle => {
+                arrow_builder.move_to(arrow_head.point);
+                arrow_builder.line_to(arrow_head.right);
+                arrow_builder.line_to(arrow_head.left);
+                arrow_builder.close();
+            }
+        }
     }
     let arrow_path = arrow_builder.build();
 
     GeometryBuilder::new().add(&arrow_path).build()
 }
+
+fn add_polyline(arrow_builder: &mut PathBuilder, polyline: &[Vec2]) {
+    let Some((first, rest)) = polyline.split_first() else {
+        return;
+    };
+    arrow_builder.move_to(*first);
+    rest.iter().for_each(|point| {
+        arrow_builder.line_to(*point);
+    });
+}
+
+/// Draws the polyline as dashes of `length`, separated by `gap`, continuing across its corners.
+fn add_dashes(arrow_builder: &mut PathBuilder, polyline: &[Vec2], length: f32, gap: f32) {
+    let mut in_dash = true;
+    let mut remaining = length; // of the current dash or gap
+    if let Some(first) = polyline.first() {
+        arrow_builder.move_to(*first);
+    }
+    for segment in polyline.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let segment_length = start.distance(end);
+        let mut travelled = 0.;
+        while segment_length - travelled > remaining {
+            travelled += remaining;
+            let point = start.lerp(end, travelled / segment_length);
+            if in_dash {
+                arrow_builder.line_to(point);
+            } else {
+                arrow_builder.move_to(point);
+            }
+            in_dash = !in_dash;
+            remaining = if in_dash { length } else { gap };
+        }
+        remaining -= segment_length - travelled;
+        if in_dash {
+            arrow_builder.line_to(end);
+        }
+    }
+}
+
+/// Shifts the polyline sideways by `offset`, to the left of its direction.
+fn offset_polyline(polyline: &[Vec2], offset: f32) -> Vec<Vec2> {
+    (0..polyline.len())
+        .map(|index| {
+            let before = polyline[index.saturating_sub(1)];
+            let after = polyline[(index + 1).min(polyline.len() - 1)];
+            let normal = (after - before).perp().normalize_or_zero();
+            polyline[index] + normal * offset
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simplify_path_keeps_short_paths() {
+        let a = Vec2::new(0., 0.);
+        let b = Vec2::new(100., 50.);
+        assert_eq!(simplify_path(&[], 10.), Vec::<Vec2>::new());
+        assert_eq!(simplify_path(&[a], 10.), vec![a]);
+        assert_eq!(simplify_path(&[a, b], 10.), vec![a, b]);
+    }
+
+    #[test]
+    fn simplify_path_drops_jitter() {
+        let points = [
+            Vec2::new(0., 0.),
+            Vec2::new(100., 3.),
+            Vec2::new(200., -4.),
+            Vec2::new(300., 2.),
+            Vec2::new(400., 0.),
+        ];
+        assert_eq!(
+            simplify_path(&points, 10.),
+            vec![Vec2::new(0., 0.), Vec2::new(400., 0.)]
+        );
+    }
+
+    #[test]
+    fn simplify_path_keeps_corners() {
+        let points = [
+            Vec2::new(0., 0.),
+            Vec2::new(100., 0.),
+            Vec2::new(200., 0.),
+            Vec2::new(200., 100.),
+            Vec2::new(200., 200.),
+        ];
+        assert_eq!(
+            simplify_path(&points, 20.),
+            vec![
+                Vec2::new(0., 0.),
+                Vec2::new(200., 0.),
+                Vec2::new(200., 200.)
+            ]
+        );
+    }
+
+    #[test]
+    fn smooth_path_passes_through_all_points() {
+        assert!(smooth_path(&[]).is_empty());
+        assert!(smooth_path(&[Vec2::ZERO]).is_empty());
+
+        let points = [
+            Vec2::new(0., 0.),
+            Vec2::new(100., 100.),
+            Vec2::new(200., 0.),
+            Vec2::new(300., 100.),
+        ];
+        let segments = smooth_path(&points);
+        assert_eq!(segments.len(), points.len() - 1);
+        for (index, [from, _, _, to]) in segments.iter().enumerate() {
+            assert_eq!(*from, points[index]);
+            assert_eq!(*to, points[index + 1]);
+        }
+    }
+
+    #[test]
+    fn smooth_path_of_two_points_is_straight() {
+        let (from, to) = (Vec2::new(0., 0.), Vec2::new(600., 300.));
+        let [_, control_from, control_to, _] = smooth_path(&[from, to])[0];
+        assert!(distance_to_segment(control_from, from, to) < 1e-3);
+        assert!(distance_to_segment(control_to, from, to) < 1e-3);
+    }
+
+    #[test]
+    fn from_sketch_needs_two_points() {
+        assert_eq!(Arrow::from_sketch(&[]), None);
+        assert_eq!(Arrow::from_sketch(&[Vec2::new(10., 10.)]), None);
+    }
+
+    #[test]
+    fn from_sketch_discards_short_paths() {
+        let returning = [
+            Vec2::new(0., 0.),
+            Vec2::new(10., 5.),
+            Vec2::new(5., 10.),
+            Vec2::new(0., 0.),
+        ];
+        assert_eq!(Arrow::from_sketch(&returning), None);
+        let short = [Vec2::new(0., 0.), Vec2::new(20., 0.)];
+        assert_eq!(Arrow::from_sketch(&short), None);
+    }
+
+    #[test]
+    fn from_sketch_straightens_almost_straight_paths() {
+        let sketch = [
+            Vec2::new(0., 0.),
+            Vec2::new(100., 5.),
+            Vec2::new(200., -5.),
+            Vec2::new(300., 0.),
+        ];
+        assert_eq!(
+            Arrow::from_sketch(&sketch),
+            Some(Arrow::Straight {
+                from: Vec2::new(0., 0.),
+                to: Vec2::new(300., 0.),
+            })
+        );
+    }
+
+    #[test]
+    fn from_sketch_keeps_curved_paths() {
+        let sketch = [
+            Vec2::new(0., 0.),
+            Vec2::new(250., 0.),
+            Vec2::new(500., 0.),
+            Vec2::new(500., 250.),
+            Vec2::new(500., 500.),
+        ];
+        assert_eq!(
+            Arrow::from_sketch(&sketch),
+            Some(Arrow::Freehand {
+                points: vec![
+                    Vec2::new(0., 0.),
+                    Vec2::new(500., 0.),
+                    Vec2::new(500., 500.)
+                ],
+            })
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use crate::bevy::{
+    arrow::{Arrow, ArrowAnchors},
+    camera::ZoomState,
+    history::{Edit, EditHistory},
+    input::InputMode,
+    selection::Selected,
+    snapping::{DragOffset, Snapping},
+    unit::{Unit, UNIT_RADIUS},
+    Z_LEVEL_ARROW_HANDLES,
+};
+use bevy::{color::palettes::css::*, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+pub struct ArrowHandlePlugin;
+impl Plugin for ArrowHandlePlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_systems(Update, sys_sync_arrow_handles);
+    }
+}
+
+const HANDLE_RADIUS: f32 = 25.;
+
+/// The point of an arrow which is moved by dragging a handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HandleKind {
+    From,
+    To,
+    ControlFrom,
+    ControlTo,
+    Midpoint, // bends the arrow, turning straight arrows into curves
+}
+impl HandleKind {
+    fn for_arrow(arrow: &Arrow) -> &'static [HandleKind] {
+        match arrow {
+            Arrow::Straight { .. } | Arrow::Freehand { .. } => {
+                &[HandleKind::From, HandleKind::To, HandleKind::Midpoint]
+            }
+            Arrow::Bezier { .. } => &[
+                HandleKind::From,
+                HandleKind::To,
+                HandleKind::ControlFrom,
+                HandleKind::ControlTo,
+                HandleKind::Midpoint,
+            ],
+        }
+    }
+
+    fn position(&self, arrow: &Arrow) -> Option<Vec2> {
+        match (self, arrow) {
+            (HandleKind::From, _) => Some(arrow.start()),
+            (HandleKind::To, _) => Some(arrow.end()),
+            (HandleKind::ControlFrom, Arrow::Bezier { control_from, .. }) => Some(*control_from),
+            (HandleKind::ControlTo, Arrow::Bezier { control_to, .. }) => Some(*control_to),
+            (HandleKind::Midpoint, _) => Some(arrow.midpoint()),
+            (
+                HandleKind::ControlFrom | HandleKind::ControlTo,
+                Arrow::Straight { .. } | Arrow::Freehand { .. },
+            ) => None,
+        }
+    }
+
+    fn moved(&self, arrow: &Arrow, delta: Vec2) -> Arrow {
+        if *self == HandleKind::Midpoint {
+            return arrow.with_midpoint_moved(delta);
+        }
+
+        let mut arrow = arrow.clone();
+        match (self, &mut arrow) {
+            (HandleKind::From, Arrow::Straight { from, .. } | Arrow::Bezier { from, .. }) => {
+                *from += delta
+            }
+            (HandleKind::To, Arrow::Straight { to, .. } | Arrow::Bezier { to, .. }) => *to += delta,
+            (HandleKind::From, Arrow::Freehand { points }) => {
+                if let Some(from) = points.first_mut() {
+                    *from += delta;
+                }
+            }
+            (HandleKind::To, Arrow::Freehand { points }) => {
+                if let Some(to) = points.last_mut() {
+                    *to += delta;
+                }
+            }
+            (HandleKind::ControlFrom, Arrow::Bezier { control_from, .. }) => *control_from += delta,
+            (HandleKind::ControlTo, Arrow::Bezier { control_to, .. }) => *control_to += delta,
+            (HandleKind::Midpoint, _)
+            | (
+                HandleKind::ControlFrom | HandleKind::ControlTo,
+                Arrow::Straight { .. } | Arrow::Freehand { .. },
+            ) => {}
+        }
+        arrow
+    }
+
+    /// Attaches the moved end of the arrow to the unit it was dropped onto, or detaches it.
+    fn anchored(&self, anchors: &ArrowAnchors, unit: Option<Entity>) -> ArrowAnchors {
+        match self {
+            HandleKind::From => ArrowAnchors {
+                from: unit,
+                ..*anchors
+            },
+            HandleKind::To => ArrowAnchors {
+                to: unit,
+                ..*anchors
+            },
+            HandleKind::ControlFrom | HandleKind::ControlTo | HandleKind::Midpoint => *anchors,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug)]
+struct ArrowHandle {
+    arrow: Entity,
+    kind: HandleKind,
+}
+
+/// The arrow as it was when its handle started being dragged, so the whole drag can be undone at
+/// once.
+#[derive(Component)]
+struct HandleDragStartArrow(Arrow, ArrowAnchors);
+
+/// Shows handles for the points of selected arrows, while arrows can be edited.
+fn sys_sync_arrow_handles(
+    current_input_mode: Res<State<InputMode>>,
+    q_selected_arrows: Query<(Entity, &Arrow), With<Selected>>,
+    mut q_handles: Query<(Entity, &ArrowHandle, &mut Transform)>,
+    mut commands: Commands,
+) {
+    let show_handles = **current_input_mode != InputMode::View;
+
+    let mut existing_handles = Vec::new();
+    for (entity, handle, mut transform) in q_handles.iter_mut() {
+        let position = q_selected_arrows
+            .get(handle.arrow)
+            .ok()
+            .filter(|_| show_handles)
+            .and_then(|(_, arrow)| handle.kind.position(arrow));
+        match position {
+            Some(position) => {
+                transform.translation = position.extend(Z_LEVEL_ARROW_HANDLES);
+                existing_handles.push((handle.arrow, handle.kind));
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    if !show_handles {
+        return;
+    }
+    for (arrow_entity, arrow) in q_selected_arrows.iter() {
+        HandleKind::for_arrow(arrow)
+            .iter()
+            .filter(|kind| !existing_handles.contains(&(arrow_entity, **kind)))
+            .for_each(|kind| {
+                if let Some(position) = kind.position(arrow) {
+                    spawn_handle(
+                        ArrowHandle {
+                            arrow: arrow_entity,
+                            kind: *kind,
+                        },
+                        position,
+                        &mut commands,
+                    );
+                }
+            });
+    }
+}
+
+fn spawn_handle(handle: ArrowHandle, position: Vec2, commands: &mut Commands) {
+    commands
+        .spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Circle {
+                    radius: HANDLE_RADIUS,
+                    center: Vec2::ZERO,
+                }),
+                transform: Transform::from_translation(position.extend(Z_LEVEL_ARROW_HANDLES)),
+                ..default()
+            },
+            Fill::color(WHITE),
+            Stroke::new(DARK_ORANGE, 5.),
+            handle,
+        ))
+        .observe(on_handle_drag_started_do_remember_arrow)
+        .observe(on_handle_dragged_do_move_point)
+        .observe(on_handle_drag_ended_do_record_change);
+}
+
+fn on_handle_drag_started_do_remember_arrow(
+    trigger: Trigger<Pointer<DragStart>>,
+    q_handles: Query<&ArrowHandle>,
+    q_arrows: Query<(&Arrow, &ArrowAnchors)>,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let Ok(handle) = q_handles.get(trigger.target) else {
+        return;
+    };
+    if let Ok((arrow, anchors)) = q_arrows.get(handle.arrow) {
+        commands.entity(trigger.target).insert((
+            HandleDragStartArrow(arrow.clone(), *anchors),
+            DragOffset::default(),
+        ));
+    }
+}
+
+/// The ends of the arrow snap into place, the other points follow the pointer freely.
+fn on_handle_dragged_do_move_point(
+    trigger: Trigger<Pointer<Drag>>,
+    mut q_handles: Query<(&ArrowHandle, &HandleDragStartArrow, &mut DragOffset)>,
+    mut q_arrows: Query<&mut Arrow>,
+    q_units: Query<&Transform, With<Unit>>,
+    r_zoom_state: Res<ZoomState>,
+    mut snapping: Snapping,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let Ok((handle, HandleDragStartArrow(before, _), mut offset)) =
+        q_handles.get_mut(trigger.target)
+    else {
+        return;
+    };
+    if let Ok(mut arrow) = q_arrows.get_mut(handle.arrow) {
+        let mut delta = trigger.delta;
+        delta.y *= -1.;
+        delta *= r_zoom_state.current_zoom_factor;
+        offset.0 += delta;
+
+        let end_start = handle
+            .kind
+            .position(before)
+            .filter(|_| matches!(handle.kind, HandleKind::From | HandleKind::To));
+        *arrow = match end_start {
+            Some(start) => {
+                let snapped = snapping.snap(
+                    start + offset.0,
+                    q_units.iter().map(|transform| transform.translation.xy()),
+                    r_zoom_state.current_zoom_factor,
+                );
+                handle.kind.moved(before, snapped - start)
+            }
+            None => handle.kind.moved(&arrow, delta),
+        };
+    }
+}
+
+fn on_handle_drag_ended_do_record_change(
+    trigger: Trigger<Pointer<DragEnd>>,
+    q_handles: Query<(&ArrowHandle, &HandleDragStartArrow)>,
+    mut q_arrows: Query<(&mut Arrow, &mut ArrowAnchors)>,
+    q_units: Query<(Entity, &Transform), With<Unit>>,
+    mut r_history: ResMut<EditHistory>,
+    mut snapping: Snapping,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    snapping.end();
+    if let Ok((handle, HandleDragStartArrow(before, anchors_before))) =
+        q_handles.get(trigger.target)
+    {
+        if let Ok((mut arrow, mut anchors)) = q_arrows.get_mut(handle.arrow) {
+            // ends dropped onto a unit stick to it, ends dragged away from their unit come loose
+            let end_position = handle
+                .kind
+                .position(&arrow)
+                .filter(|_| matches!(handle.kind, HandleKind::From | HandleKind::To));
+            if let Some(position) = end_position {
+                let target_unit = q_units.iter().find(|(_, transform)| {
+                    transform.translation.xy().distance(position) <= UNIT_RADIUS
+                });
+                if let Some((_, target_transform)) = target_unit {
+                    let delta = target_transform.translation.xy() - position;
+                    *arrow = handle.kind.moved(&arrow, delta);
+                }
+                anchors.set_if_neq(
+                    handle
+                        .kind
+                        .anchored(&anchors, target_unit.map(|(entity, _)| entity)),
+                );
+            }
+
+            if before != &*arrow || anchors_before != &*anchors {
+                r_history.record(Edit::ChangeArrow {
+                    arrow_entity: handle.arrow,
+                    before: before.clone(),
+                    after: arrow.clone(),
+                    anchors_before: *anchors_before,
+                    anchors_after: *anchors,
+                });
+            }
+        }
+        commands
+            .entity(trigger.target)
+            .remove::<(HandleDragStartArrow, DragOffset)>();
+    }
+}
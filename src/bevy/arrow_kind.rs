@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct ArrowKindPlugin;
+impl Plugin for ArrowKindPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<CurrentArrowKind>()
+            .add_event::<SelectArrowKind>()
+            .add_systems(
+                Update,
+                sys_select_arrow_kind.run_if(on_event::<SelectArrowKind>),
+            );
+    }
+}
+
+/// What an arrow stands for, which determines how it is drawn.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ArrowKind {
+    #[default]
+    Movement,
+    JuggThrow,
+    ShieldBlock,
+    ChainSwing,
+    PompfeStrike,
+}
+impl ArrowKind {
+    pub const ALL: [ArrowKind; 5] = [
+        ArrowKind::Movement,
+        ArrowKind::JuggThrow,
+        ArrowKind::ShieldBlock,
+        ArrowKind::ChainSwing,
+        ArrowKind::PompfeStrike,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArrowKind::Movement => "Run",
+            ArrowKind::JuggThrow => "Throw",
+            ArrowKind::ShieldBlock => "Block",
+            ArrowKind::ChainSwing => "Chain swing",
+            ArrowKind::PompfeStrike => "Strike",
+        }
+    }
+
+    pub fn line_style(&self) -> LineStyle {
+        match self {
+            ArrowKind::Movement | ArrowKind::ShieldBlock => LineStyle::Solid,
+            ArrowKind::JuggThrow => LineStyle::Dashed,
+            ArrowKind::ChainSwing => LineStyle::Dotted,
+            ArrowKind::PompfeStrike => LineStyle::Double,
+        }
+    }
+
+    pub fn head_style(&self) -> HeadStyle {
+        match self {
+            ArrowKind::Movement | ArrowKind::JuggThrow | ArrowKind::ChainSwing => {
+                HeadStyle::Chevron
+            }
+            ArrowKind::ShieldBlock => HeadStyle::Bar,
+            ArrowKind::PompfeStrike => HeadStyle::Triangle,
+        }
+    }
+
+    pub fn stroke_options(&self) -> StrokeOptions {
+        let line_width = match self.line_style() {
+            LineStyle::Double => 6.,
+            LineStyle::Solid | LineStyle::Dashed | LineStyle::Dotted => 10.,
+        };
+        // round caps turn the very short dashes of dotted lines into dots
+        StrokeOptions::default()
+            .with_line_width(line_width)
+            .with_line_cap(LineCap::Round)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+    Double,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeadStyle {
+    Chevron,
+    Bar,      // stops at the end, e.g. a blocked path
+    Triangle, // closed, for hits
+}
+
+/// The kind of arrow drawn next in movement mode.
+#[derive(Resource, Default)]
+pub struct CurrentArrowKind(pub ArrowKind);
+
+/// Chooses the kind of arrow drawn next, see [`CurrentArrowKind`].
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SelectArrowKind(pub ArrowKind);
+
+fn sys_select_arrow_kind(
+    mut er_select_arrow_kind: EventReader<SelectArrowKind>,
+    mut r_current_arrow_kind: ResMut<CurrentArrowKind>,
+) {
+    if let Some(SelectArrowKind(kind)) = er_select_arrow_kind.read().last() {
+        r_current_arrow_kind.0 = *kind;
+    }
+}
@@ -1,4 +1,5 @@
-use bevy::prelude::*;
+use crate::bevy::{field::FIELD_HALF_EXTENTS, input::InputMode, SIZE_SCALING_FACTOR};
+use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_prototype_lyon::prelude::tess::geom::euclid::approxeq::ApproxEq;
 use leafwing_input_manager::prelude::*;
 
@@ -9,10 +10,13 @@ impl Plugin for CameraPlugin {
             .init_resource::<ZoomState>()
             .add_systems(Startup, sys_setup)
             .add_systems(Update, sys_zoom_camera)
+            .add_systems(Update, sys_pan_camera)
+            .add_systems(Update, sys_touch_gestures)
             .add_systems(
                 Update,
                 sys_sync_zoom_state
                     .after(sys_zoom_camera)
+                    .after(sys_touch_gestures)
                     .run_if(resource_changed::<ZoomState>),
             );
     }
@@ -22,13 +26,18 @@ impl Plugin for CameraPlugin {
 pub enum CameraAction {
     #[actionlike(Axis)]
     Zoom,
+    Grab,        // pans the view while held, only in view mode
+    GrabAnyMode, // pans the view while held, regardless of the input mode
 }
 
 fn sys_setup(mut commands: Commands) {
     commands.spawn((
         Camera2d,
         InputManagerBundle::with_map(
-            InputMap::default().with_axis(CameraAction::Zoom, MouseScrollAxis::Y),
+            InputMap::default()
+                .with_axis(CameraAction::Zoom, MouseScrollAxis::Y)
+                .with(CameraAction::Grab, MouseButton::Left)
+                .with(CameraAction::GrabAnyMode, MouseButton::Middle),
         ),
     ));
 }
@@ -40,24 +49,37 @@ const MAX_ZOOM_FACTOR: f32 = 4.5;
 pub struct ZoomState {
     pub current_zoom_factor: f32,
     zoom_rate: f32,
+    anchor: Option<Vec2>, // viewport position that stays in place while zooming, center if None
 }
 impl Default for ZoomState {
     fn default() -> Self {
         Self {
             current_zoom_factor: MAX_ZOOM_FACTOR,
             zoom_rate: 0.1,
+            anchor: None,
         }
     }
 }
 impl ZoomState {
-    fn zoom(&mut self, delta: f32) {
+    fn zoom(&mut self, delta: f32, anchor: Option<Vec2>) {
         #[cfg(target_arch = "wasm32")]
         let delta = delta / 120.;
 
-        let new_zoom_factor = (self.current_zoom_factor * (1. - delta * self.zoom_rate))
-            .clamp(MIN_ZOOM_FACTOR, MAX_ZOOM_FACTOR);
+        self.set_zoom_factor(
+            self.current_zoom_factor * (1. - delta * self.zoom_rate),
+            anchor,
+        );
+    }
+
+    fn scale_by(&mut self, factor: f32, anchor: Vec2) {
+        self.set_zoom_factor(self.current_zoom_factor * factor, Some(anchor));
+    }
+
+    fn set_zoom_factor(&mut self, zoom_factor: f32, anchor: Option<Vec2>) {
+        let new_zoom_factor = zoom_factor.clamp(MIN_ZOOM_FACTOR, MAX_ZOOM_FACTOR);
         if !self.current_zoom_factor.approx_eq(&new_zoom_factor) {
             self.current_zoom_factor = new_zoom_factor;
+            self.anchor = anchor;
         }
     }
 }
@@ -65,13 +87,111 @@ impl ZoomState {
 fn sys_zoom_camera(
     mut r_zoom_state: ResMut<ZoomState>,
     q_camera: Single<&ActionState<CameraAction>, With<Camera2d>>,
+    q_window: Single<&Window, With<PrimaryWindow>>,
 ) {
-    r_zoom_state.zoom(q_camera.value(&CameraAction::Zoom));
+    // zoom towards the pointer, falls back to the center if it isn't over the canvas
+    r_zoom_state.zoom(
+        q_camera.value(&CameraAction::Zoom),
+        q_window.cursor_position(),
+    );
 }
 
 fn sys_sync_zoom_state(
     r_zoom_state: Res<ZoomState>,
-    mut q_camera: Single<&mut OrthographicProjection, With<Camera2d>>,
+    q_camera: Single<(&Camera, &mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let (camera, mut transform, mut projection) = q_camera.into_inner();
+    let new_scale = r_zoom_state.current_zoom_factor;
+
+    // keep the world position under the anchor in place, by moving the view along with the zoom
+    if let (Some(anchor), Some(viewport_size)) =
+        (r_zoom_state.anchor, camera.logical_viewport_size())
+    {
+        let offset = (anchor - viewport_size / 2.) * Vec2::new(1., -1.);
+        let shift = offset * (projection.scale - new_scale);
+        transform.translation += shift.extend(0.);
+        clamp_to_field(&mut transform.translation);
+    }
+    projection.scale = new_scale;
+}
+
+fn sys_pan_camera(
+    q_camera: Single<
+        (
+            &ActionState<CameraAction>,
+            &mut Transform,
+            &OrthographicProjection,
+        ),
+        With<Camera2d>,
+    >,
+    current_input_mode: Res<State<InputMode>>,
+    mut er_cursor_moved: EventReader<CursorMoved>,
+    r_touches: Res<Touches>,
+) {
+    let (action_state, mut transform, projection) = q_camera.into_inner();
+    let in_view_mode = **current_input_mode == InputMode::View;
+
+    let cursor_delta = er_cursor_moved
+        .read()
+        .filter_map(|cursor_moved| cursor_moved.delta)
+        .sum::<Vec2>();
+    let mut screen_delta = Vec2::ZERO;
+    if action_state.pressed(&CameraAction::GrabAnyMode)
+        || (in_view_mode && action_state.pressed(&CameraAction::Grab))
+    {
+        screen_delta += cursor_delta;
+    }
+    if in_view_mode {
+        // multi-touch gestures are not handled here
+        if let [touch] = r_touches.iter().collect::<Vec<_>>()[..] {
+            screen_delta += touch.delta();
+        }
+    }
+    if screen_delta != Vec2::ZERO {
+        drag_view(&mut transform.translation, screen_delta, projection.scale);
+    }
+}
+
+fn sys_touch_gestures(
+    mut r_zoom_state: ResMut<ZoomState>,
+    q_camera: Single<(&mut Transform, &OrthographicProjection), With<Camera2d>>,
+    r_touches: Res<Touches>,
 ) {
-    q_camera.scale = r_zoom_state.current_zoom_factor;
+    let [first, second] = r_touches.iter().collect::<Vec<_>>()[..] else {
+        return;
+    };
+    let (mut transform, projection) = q_camera.into_inner();
+
+    let previous_centroid = (first.previous_position() + second.previous_position()) / 2.;
+    let centroid = (first.position() + second.position()) / 2.;
+    drag_view(
+        &mut transform.translation,
+        centroid - previous_centroid,
+        projection.scale,
+    );
+
+    let previous_distance = first
+        .previous_position()
+        .distance(second.previous_position());
+    let distance = first.position().distance(second.position());
+    if previous_distance > 0. && distance > 0. {
+        // spreading the fingers apart zooms in, i.e. reduces the scale
+        r_zoom_state.scale_by(previous_distance / distance, centroid);
+    }
+}
+
+/// Moves the view as if the board was dragged along by `screen_delta` (in logical pixels).
+fn drag_view(translation: &mut Vec3, screen_delta: Vec2, scale: f32) {
+    // the camera moves opposite to the board, and the y-axis of the screen points down
+    translation.x -= screen_delta.x * scale;
+    translation.y += screen_delta.y * scale;
+    clamp_to_field(translation);
+}
+
+/// Keeps the center of the view on the field, so the field can't be moved entirely off screen.
+fn clamp_to_field(translation: &mut Vec3) {
+    let max = FIELD_HALF_EXTENTS * SIZE_SCALING_FACTOR;
+    let clamped = translation.xy().clamp(-max, max);
+    translation.x = clamped.x;
+    translation.y = clamped.y;
 }
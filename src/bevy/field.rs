@@ -1,14 +1,33 @@
-use crate::bevy::{from_meters, radius_from_meters, Z_LEVEL_FIELD_BACKGROUND};
+use crate::bevy::{
+    from_meters, radius_from_meters,
+    selection::{
+        on_field_drag_ended_do_select_box, on_field_drag_started_do_start_box,
+        on_field_dragged_do_resize_box, on_field_pressed_do_clear_selection,
+    },
+    Z_LEVEL_FIELD_BACKGROUND,
+};
 use bevy::{app::Plugin, color::palettes::css::*, ecs::system::Commands, prelude::*};
 use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct FieldPlugin;
 impl Plugin for FieldPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_systems(Startup, setup_field);
+        app.init_resource::<FieldVariant>()
+            .add_systems(Startup, setup_field);
     }
 }
 
+/// Distance from the center of the field to its outermost edges, in meters.
+pub const FIELD_HALF_EXTENTS: Vec2 = Vec2::new(20., 10.);
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldVariant {
+    // TODO add further variants, e.g. for smaller training fields
+    #[default]
+    Standard,
+}
+
 fn setup_field(mut commands: Commands) {
     let field_corners = shapes::Polygon {
         points: [
@@ -60,13 +79,18 @@ fn setup_field(mut commands: Commands) {
         .add(&right_base)
         .build();
 
-    commands.spawn((
-        ShapeBundle {
-            path: field_shape,
-            transform: Transform::from_xyz(0., 0., Z_LEVEL_FIELD_BACKGROUND),
-            ..default()
-        },
-        Stroke::new(BLACK, 10.),
-        Fill::color(LIGHT_GREEN),
-    ));
+    commands
+        .spawn((
+            ShapeBundle {
+                path: field_shape,
+                transform: Transform::from_xyz(0., 0., Z_LEVEL_FIELD_BACKGROUND),
+                ..default()
+            },
+            Stroke::new(BLACK, 10.),
+            Fill::color(LIGHT_GREEN),
+        ))
+        .observe(on_field_pressed_do_clear_selection)
+        .observe(on_field_drag_started_do_start_box)
+        .observe(on_field_dragged_do_resize_box)
+        .observe(on_field_drag_ended_do_select_box);
 }
@@ -0,0 +1,367 @@
+use crate::bevy::{
+    field::FIELD_HALF_EXTENTS,
+    from_meters,
+    history::{Edit, EditHistory},
+    playback::{interrupt_playback_if, BoardChangeSet},
+    to_meters,
+    unit::{Team, Unit, UnitType},
+};
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    reflect::TypePath,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct FormationPlugin;
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_asset::<Formations>()
+            .init_asset_loader::<FormationsLoader>()
+            .init_resource::<FormationLibrary>()
+            .add_event::<ApplyFormation>()
+            .add_event::<FormationsChanged>()
+            .add_systems(
+                Update,
+                sys_apply_formation
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<ApplyFormation>),
+            )
+            .add_systems(Update, interrupt_playback_if(on_event::<ApplyFormation>))
+            .add_systems(Update, sys_animate_formation_transitions)
+            .add_systems(
+                Update,
+                sys_send_formations_changed.run_if(on_event::<AssetEvent<Formations>>),
+            );
+    }
+}
+
+/// The formations shipped with the app.
+const DEFAULT_FORMATIONS_PATH: &str = "config/default.formations.json";
+
+/// How long the units take to move into a formation, in seconds.
+const TRANSITION_DURATION: f32 = 0.6;
+
+/// Where the players of a team stand, no matter which side of the field the team plays on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Formation {
+    pub name: String,
+    #[serde(default)]
+    pub anchor: FormationAnchor,
+    pub spots: Vec<FormationSpot>,
+}
+
+/// What the spots of a formation are relative to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormationAnchor {
+    /// The center of the team's base line.
+    #[default]
+    Side,
+    /// The jugg, wherever it is.
+    Jugg,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct FormationSpot {
+    /// In meters, x points towards the opposing side and y to the left when facing it.
+    pub position: Vec2,
+    /// Whether the spot is meant for the runner.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub runner: bool,
+}
+
+impl Formation {
+    /// Turns the spots into board positions for the team, in pixels.
+    fn spot_positions(&self, team: Team, jugg: Option<Vec2>) -> Vec<(Vec2, bool)> {
+        let forward = match team {
+            Team::Left => Vec2::X,
+            Team::Right => Vec2::NEG_X,
+        };
+        let origin = match (self.anchor, jugg) {
+            (FormationAnchor::Side, _) | (FormationAnchor::Jugg, None) => {
+                -forward * FIELD_HALF_EXTENTS.x
+            }
+            (FormationAnchor::Jugg, Some(jugg)) => to_meters(jugg),
+        };
+        self.spots
+            .iter()
+            .map(|spot| {
+                let position =
+                    origin + forward * spot.position.x + forward.perp() * spot.position.y;
+                (from_meters(position.x, position.y), spot.runner)
+            })
+            .collect()
+    }
+
+    /// Pairs each player with the closest free spot, runners taking runner spots first.
+    ///
+    /// Returns each player along with where it is and where it goes. Players left over when there
+    /// are more players than spots stay where they are.
+    fn assign(
+        &self,
+        team: Team,
+        jugg: Option<Vec2>,
+        players: &[(Entity, Vec2, bool)],
+    ) -> Vec<(Entity, Vec2, Vec2)> {
+        let mut free_spots = self.spot_positions(team, jugg);
+        let mut players = players.to_vec();
+        players.sort_by_key(|(_, _, is_runner)| !is_runner);
+
+        let mut assignments = Vec::new();
+        for (entity, position, is_runner) in players {
+            let distance = |index: &usize| free_spots[*index].0.distance_squared(position);
+            let closest = |matching: bool| {
+                (0..free_spots.len())
+                    .filter(|index| !matching || free_spots[*index].1 == is_runner)
+                    .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            };
+            if let Some(index) = closest(true).or_else(|| closest(false)) {
+                assignments.push((entity, position, free_spots.remove(index).0));
+            }
+        }
+        assignments
+    }
+}
+
+/// A file of formations, see [`DEFAULT_FORMATIONS_PATH`].
+#[derive(Asset, TypePath, Clone, Debug, Serialize, Deserialize)]
+pub struct Formations {
+    pub formations: Vec<Formation>,
+}
+
+#[derive(Default)]
+struct FormationsLoader;
+impl AssetLoader for FormationsLoader {
+    type Asset = Formations;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Formations, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["formations.json"]
+    }
+}
+
+/// The formations to choose from.
+#[derive(Resource)]
+struct FormationLibrary {
+    formations: Handle<Formations>,
+}
+impl FromWorld for FormationLibrary {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            formations: world
+                .resource::<AssetServer>()
+                .load(DEFAULT_FORMATIONS_PATH),
+        }
+    }
+}
+
+/// Moves the players of the team into the formation of the given name.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct ApplyFormation {
+    pub team: Team,
+    pub formation: String,
+}
+
+/// Tells the UI which formations there are to choose from.
+#[derive(Event, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormationsChanged(pub Vec<String>);
+
+/// A unit on its way into a formation.
+#[derive(Component)]
+struct FormationTransition {
+    from: Vec2,
+    to: Vec2,
+    elapsed: f32,
+    position: Vec2, // where the transition put the unit last
+}
+
+fn sys_apply_formation(
+    mut er_apply_formation: EventReader<ApplyFormation>,
+    r_formation_library: Res<FormationLibrary>,
+    r_formations: Res<Assets<Formations>>,
+    q_units: Query<(Entity, &Unit, &Transform)>,
+    mut r_history: ResMut<EditHistory>,
+    mut commands: Commands,
+) {
+    for ApplyFormation { team, formation } in er_apply_formation.read() {
+        let Some(formation) = r_formations
+            .get(&r_formation_library.formations)
+            .and_then(|formations| formations.formations.iter().find(|f| f.name == *formation))
+        else {
+            warn!("There is no formation named {formation}.");
+            continue;
+        };
+
+        let jugg = q_units
+            .iter()
+            .find(|(_, unit, _)| **unit == Unit::Jugg)
+            .map(|(_, _, transform)| transform.translation.xy());
+        let players = q_units
+            .iter()
+            .filter_map(|(entity, unit, transform)| match unit {
+                Unit::Player {
+                    team: unit_team,
+                    unit_type,
+                    ..
+                } if unit_team == team => Some((
+                    entity,
+                    transform.translation.xy(),
+                    matches!(unit_type, UnitType::Runner { .. }),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut moves = Vec::new();
+        for (entity, from, to) in formation.assign(*team, jugg, &players) {
+            commands.entity(entity).insert(FormationTransition {
+                from,
+                to,
+                elapsed: 0.,
+                position: from,
+            });
+            moves.push(Edit::MoveUnit {
+                unit: entity,
+                from,
+                to,
+            });
+        }
+        if let Some(edit) = Edit::group(moves) {
+            r_history.record(edit);
+        }
+    }
+}
+
+fn sys_animate_formation_transitions(
+    time: Res<Time>,
+    mut q_transitions: Query<(Entity, &mut FormationTransition, &mut Transform)>,
+    mut commands: Commands,
+) {
+    for (entity, mut transition, mut transform) in q_transitions.iter_mut() {
+        // the unit was moved by other means in the meantime, e.g. by undoing the formation
+        if transform.translation.xy() != transition.position {
+            commands.entity(entity).remove::<FormationTransition>();
+            continue;
+        }
+        transition.elapsed += time.delta_secs();
+        let progress = (transition.elapsed / TRANSITION_DURATION).min(1.);
+        // ease in and out, so the units don't jerk into motion
+        let eased = progress * progress * (3. - 2. * progress);
+        let position = transition.from.lerp(transition.to, eased);
+        transform.translation = position.extend(transform.translation.z);
+        transition.position = position;
+        if progress >= 1. {
+            commands.entity(entity).remove::<FormationTransition>();
+        }
+    }
+}
+
+fn sys_send_formations_changed(
+    r_formation_library: Res<FormationLibrary>,
+    r_formations: Res<Assets<Formations>>,
+    mut ew_formations_changed: EventWriter<FormationsChanged>,
+) {
+    let names = r_formations
+        .get(&r_formation_library.formations)
+        .map_or_else(Vec::new, |formations| {
+            formations
+                .formations
+                .iter()
+                .map(|formation| formation.name.clone())
+                .collect()
+        });
+    ew_formations_changed.send(FormationsChanged(names));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formation(anchor: FormationAnchor, spots: &[(Vec2, bool)]) -> Formation {
+        Formation {
+            name: "Test".to_owned(),
+            anchor,
+            spots: spots
+                .iter()
+                .map(|(position, runner)| FormationSpot {
+                    position: *position,
+                    runner: *runner,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn assign_gives_runners_the_runner_spots() {
+        let formation = formation(
+            FormationAnchor::Side,
+            &[(Vec2::new(1., 0.), true), (Vec2::new(10., 0.), false)],
+        );
+        let spots = formation.spot_positions(Team::Left, None);
+        let (runner, player) = (Entity::from_raw(1), Entity::from_raw(2));
+        // each one stands on the spot meant for the other
+        let players = [(player, spots[0].0, false), (runner, spots[1].0, true)];
+        assert_eq!(
+            formation.assign(Team::Left, None, &players),
+            vec![
+                (runner, spots[1].0, spots[0].0),
+                (player, spots[0].0, spots[1].0),
+            ]
+        );
+    }
+
+    #[test]
+    fn assign_leaves_players_without_spot_in_place() {
+        let formation = formation(FormationAnchor::Side, &[(Vec2::new(1., 0.), false)]);
+        let players = [
+            (Entity::from_raw(1), Vec2::ZERO, false),
+            (Entity::from_raw(2), Vec2::ZERO, false),
+        ];
+        let assignments = formation.assign(Team::Left, None, &players);
+        assert_eq!(assignments.len(), 1);
+        assert_eq!(assignments[0].0, Entity::from_raw(1));
+    }
+
+    #[test]
+    fn assign_faces_the_opposing_side() {
+        let formation = formation(FormationAnchor::Side, &[(Vec2::new(1., 2.), false)]);
+        let player = [(Entity::from_raw(1), Vec2::ZERO, false)];
+        let half_length = FIELD_HALF_EXTENTS.x;
+        assert_eq!(
+            formation.assign(Team::Left, None, &player)[0].2,
+            from_meters(-half_length + 1., 2.)
+        );
+        assert_eq!(
+            formation.assign(Team::Right, None, &player)[0].2,
+            from_meters(half_length - 1., -2.)
+        );
+    }
+
+    #[test]
+    fn assign_follows_the_jugg() {
+        let formation = formation(FormationAnchor::Jugg, &[(Vec2::new(-1., 0.), false)]);
+        let jugg = from_meters(3., 4.);
+        let player = [(Entity::from_raw(1), Vec2::ZERO, false)];
+        assert_eq!(
+            formation.assign(Team::Left, Some(jugg), &player)[0].2,
+            from_meters(2., 4.)
+        );
+        // without a jugg on the board, the formation is relative to the side
+        assert_eq!(
+            formation.assign(Team::Left, None, &player)[0].2,
+            from_meters(-FIELD_HALF_EXTENTS.x - 1., 0.)
+        );
+    }
+}
@@ -0,0 +1,549 @@
+use crate::bevy::{
+    arrow::{spawn_arrow, Arrow, ArrowAnchors},
+    arrow_kind::ArrowKind,
+    input::GlobalAction,
+    playback::{interrupt_playback_if, BoardChangeSet},
+    scenario::{LoadScenario, RosterIndex},
+    unit::{spawn_unit_at, Unit, UnitDetails},
+};
+use bevy::prelude::*;
+use leafwing_input_manager::{common_conditions::action_just_pressed, prelude::*};
+
+pub struct HistoryPlugin;
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditHistory>()
+            .add_event::<HistoryAction>()
+            .add_event::<HistoryChanged>()
+            .add_systems(Update, sys_apply_history_action.in_set(BoardChangeSet))
+            .add_systems(
+                Update,
+                interrupt_playback_if(
+                    on_event::<HistoryAction>
+                        .or(action_just_pressed(GlobalAction::Undo))
+                        .or(action_just_pressed(GlobalAction::Redo)),
+                ),
+            )
+            .add_systems(Update, sys_clear_history.run_if(on_event::<LoadScenario>))
+            .add_systems(
+                Update,
+                sys_send_history_changed.run_if(resource_changed::<EditHistory>),
+            );
+    }
+}
+
+/// Limits how many edits can be undone, so the history doesn't grow indefinitely.
+const MAX_HISTORY_LENGTH: usize = 100;
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+/// Tells the UI whether the board was modified, i.e. which edits there are to undo or redo.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HistoryChanged {
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// A single, reversible change to the board.
+///
+/// A whole user gesture (e.g. dragging a unit) is recorded as one edit, so it is undone in one step.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    MoveUnit {
+        unit: Entity,
+        from: Vec2,
+        to: Vec2,
+    },
+    /// The roster index is kept, so a restored unit takes its old place in the other steps.
+    CreateUnit {
+        unit_entity: Entity,
+        unit: Unit,
+        details: UnitDetails,
+        position: Vec2,
+        roster_index: Option<RosterIndex>,
+    },
+    DeleteUnit {
+        unit_entity: Entity,
+        unit: Unit,
+        details: UnitDetails,
+        position: Vec2,
+        roster_index: Option<RosterIndex>,
+    },
+    /// E.g. changing the team, type or state of a unit, or what is noted about it.
+    ChangeUnit {
+        unit: Entity,
+        before: Unit,
+        after: Unit,
+        details_before: UnitDetails,
+        details_after: UnitDetails,
+    },
+    CreateArrow {
+        arrow_entity: Entity,
+        arrow: Arrow,
+        kind: ArrowKind,
+        anchors: ArrowAnchors,
+    },
+    DeleteArrow {
+        arrow_entity: Entity,
+        arrow: Arrow,
+        kind: ArrowKind,
+        anchors: ArrowAnchors,
+    },
+    ChangeArrow {
+        arrow_entity: Entity,
+        before: Arrow,
+        after: Arrow,
+        anchors_before: ArrowAnchors,
+        anchors_after: ArrowAnchors,
+    },
+    /// Several edits made by one gesture, e.g. moving all selected units at once.
+    Group(Vec<Edit>),
+}
+impl Edit {
+    /// Combines the edits into one, or returns `None` if there are none.
+    pub fn group(mut edits: Vec<Edit>) -> Option<Edit> {
+        match edits.len() {
+            0 => None,
+            1 => edits.pop(),
+            _ => Some(Edit::Group(edits)),
+        }
+    }
+
+    fn inverse(&self) -> Edit {
+        match self.clone() {
+            Edit::MoveUnit { unit, from, to } => Edit::MoveUnit {
+                unit,
+                from: to,
+                to: from,
+            },
+            Edit::CreateUnit {
+                unit_entity,
+                unit,
+                details,
+                position,
+                roster_index,
+            } => Edit::DeleteUnit {
+                unit_entity,
+                unit,
+                details,
+                position,
+                roster_index,
+            },
+            Edit::DeleteUnit {
+                unit_entity,
+                unit,
+                details,
+                position,
+                roster_index,
+            } => Edit::CreateUnit {
+                unit_entity,
+                unit,
+                details,
+                position,
+                roster_index,
+            },
+            Edit::ChangeUnit {
+                unit,
+                before,
+                after,
+                details_before,
+                details_after,
+            } => Edit::ChangeUnit {
+                unit,
+                before: after,
+                after: before,
+                details_before: details_after,
+                details_after: details_before,
+            },
+            Edit::CreateArrow {
+                arrow_entity,
+                arrow,
+                kind,
+                anchors,
+            } => Edit::DeleteArrow {
+                arrow_entity,
+                arrow,
+                kind,
+                anchors,
+            },
+            Edit::DeleteArrow {
+                arrow_entity,
+                arrow,
+                kind,
+                anchors,
+            } => Edit::CreateArrow {
+                arrow_entity,
+                arrow,
+                kind,
+                anchors,
+            },
+            Edit::ChangeArrow {
+                arrow_entity,
+                before,
+                after,
+                anchors_before,
+                anchors_after,
+            } => Edit::ChangeArrow {
+                arrow_entity,
+                before: after,
+                after: before,
+                anchors_before: anchors_after,
+                anchors_after: anchors_before,
+            },
+            Edit::Group(edits) => Edit::Group(edits.iter().rev().map(Edit::inverse).collect()),
+        }
+    }
+
+    /// Performs the edit on the board.
+    ///
+    /// Entities which had to be re-created are returned as `(old, new)`, so that other edits
+    /// referring to them can be updated.
+    fn apply(
+        &self,
+        q_units: &mut HistoryUnitQuery,
+        q_arrows: &mut Query<(&mut Arrow, &mut ArrowAnchors)>,
+        r_asset_server: &Res<AssetServer>,
+        commands: &mut Commands,
+    ) -> Vec<(Entity, Entity)> {
+        match self {
+            Edit::MoveUnit { unit, to, .. } => {
+                if let Ok((mut transform, ..)) = q_units.get_mut(*unit) {
+                    transform.translation = to.extend(transform.translation.z);
+                }
+                vec![]
+            }
+            Edit::ChangeUnit {
+                unit,
+                after,
+                details_after,
+                ..
+            } => {
+                if let Ok((_, mut unit_component, mut details)) = q_units.get_mut(*unit) {
+                    *unit_component = *after;
+                    *details = details_after.clone();
+                }
+                vec![]
+            }
+            Edit::CreateUnit {
+                unit_entity,
+                unit,
+                details,
+                position,
+                roster_index,
+            } => {
+                let new_entity = spawn_unit_at(*unit, *position, commands, r_asset_server);
+                commands.entity(new_entity).insert(details.clone());
+                if let Some(roster_index) = roster_index {
+                    commands.entity(new_entity).insert(*roster_index);
+                }
+                vec![(*unit_entity, new_entity)]
+            }
+            Edit::DeleteUnit { unit_entity, .. } => {
+                if let Some(entity_commands) = commands.get_entity(*unit_entity) {
+                    entity_commands.despawn_recursive();
+                }
+                vec![]
+            }
+            Edit::CreateArrow {
+                arrow_entity,
+                arrow,
+                kind,
+                anchors,
+            } => vec![(
+                *arrow_entity,
+                spawn_arrow(arrow.clone(), *kind, *anchors, commands),
+            )],
+            Edit::DeleteArrow { arrow_entity, .. } => {
+                if let Some(mut entity_commands) = commands.get_entity(*arrow_entity) {
+                    entity_commands.despawn();
+                }
+                vec![]
+            }
+            Edit::ChangeArrow {
+                arrow_entity,
+                after,
+                anchors_after,
+                ..
+            } => {
+                if let Ok((mut arrow, mut anchors)) = q_arrows.get_mut(*arrow_entity) {
+                    *arrow = after.clone();
+                    *anchors = *anchors_after;
+                }
+                vec![]
+            }
+            Edit::Group(edits) => {
+                // later edits of the group may refer to entities re-created by earlier ones
+                let mut remapped = Vec::new();
+                for edit in edits {
+                    let mut edit = edit.clone();
+                    for (old, new) in &remapped {
+                        edit.remap(*old, *new);
+                    }
+                    remapped.extend(edit.apply(q_units, q_arrows, r_asset_server, commands));
+                }
+                remapped
+            }
+        }
+    }
+
+    fn remap(&mut self, old: Entity, new: Entity) {
+        let entity = match self {
+            Edit::Group(edits) => {
+                edits.iter_mut().for_each(|edit| edit.remap(old, new));
+                return;
+            }
+            Edit::MoveUnit { unit, .. } | Edit::ChangeUnit { unit, .. } => unit,
+            Edit::CreateUnit { unit_entity, .. } | Edit::DeleteUnit { unit_entity, .. } => {
+                unit_entity
+            }
+            Edit::CreateArrow {
+                arrow_entity,
+                anchors,
+                ..
+            }
+            | Edit::DeleteArrow {
+                arrow_entity,
+                anchors,
+                ..
+            } => {
+                anchors.remap(old, new);
+                arrow_entity
+            }
+            Edit::ChangeArrow {
+                arrow_entity,
+                anchors_before,
+                anchors_after,
+                ..
+            } => {
+                anchors_before.remap(old, new);
+                anchors_after.remap(old, new);
+                arrow_entity
+            }
+        };
+        if *entity == old {
+            *entity = new;
+        }
+    }
+}
+
+type HistoryUnitQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Unit,
+        &'static mut UnitDetails,
+    ),
+>;
+
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+}
+impl EditHistory {
+    pub fn record(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+        self.undo_stack.push(edit);
+        if self.undo_stack.len() > MAX_HISTORY_LENGTH {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    fn remap(&mut self, old: Entity, new: Entity) {
+        self.undo_stack
+            .iter_mut()
+            .chain(self.redo_stack.iter_mut())
+            .for_each(|edit| edit.remap(old, new));
+    }
+}
+
+fn sys_apply_history_action(
+    action_state: Res<ActionState<GlobalAction>>,
+    mut er_history_action: EventReader<HistoryAction>,
+    mut r_history: ResMut<EditHistory>,
+    mut q_units: HistoryUnitQuery,
+    mut q_arrows: Query<(&mut Arrow, &mut ArrowAnchors)>,
+    r_asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let key_actions = [
+        (GlobalAction::Undo, HistoryAction::Undo),
+        (GlobalAction::Redo, HistoryAction::Redo),
+    ]
+    .into_iter()
+    .filter(|(global_action, _)| action_state.just_pressed(global_action))
+    .map(|(_, history_action)| history_action)
+    .collect::<Vec<_>>();
+
+    for history_action in er_history_action.read().copied().chain(key_actions) {
+        let (performed, edit) = match history_action {
+            HistoryAction::Undo => match r_history.undo_stack.pop() {
+                Some(edit) => (edit.inverse(), edit),
+                None => continue,
+            },
+            HistoryAction::Redo => match r_history.redo_stack.pop() {
+                Some(edit) => (edit.clone(), edit),
+                None => continue,
+            },
+        };
+
+        let remapped = performed.apply(&mut q_units, &mut q_arrows, &r_asset_server, &mut commands);
+        match history_action {
+            HistoryAction::Undo => r_history.redo_stack.push(edit),
+            HistoryAction::Redo => r_history.undo_stack.push(edit),
+        }
+        for (old, new) in remapped {
+            r_history.remap(old, new);
+        }
+    }
+}
+
+fn sys_clear_history(mut r_history: ResMut<EditHistory>) {
+    r_history.clear();
+}
+
+fn sys_send_history_changed(
+    r_history: Res<EditHistory>,
+    mut ew_history_changed: EventWriter<HistoryChanged>,
+) {
+    ew_history_changed.send(HistoryChanged {
+        can_undo: !r_history.undo_stack.is_empty(),
+        can_redo: !r_history.redo_stack.is_empty(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn move_unit(unit: Entity, from: f32, to: f32) -> Edit {
+        Edit::MoveUnit {
+            unit,
+            from: Vec2::splat(from),
+            to: Vec2::splat(to),
+        }
+    }
+
+    #[test]
+    fn group_only_wraps_several_edits() {
+        let edit = move_unit(Entity::from_raw(1), 0., 1.);
+        assert_eq!(Edit::group(vec![]), None);
+        assert_eq!(Edit::group(vec![edit.clone()]), Some(edit.clone()));
+        assert_eq!(
+            Edit::group(vec![edit.clone(), edit.clone()]),
+            Some(Edit::Group(vec![edit.clone(), edit]))
+        );
+    }
+
+    #[test]
+    fn inverse_swaps_before_and_after() {
+        let unit = Entity::from_raw(1);
+        assert_eq!(move_unit(unit, 0., 1.).inverse(), move_unit(unit, 1., 0.));
+
+        let create = Edit::CreateUnit {
+            unit_entity: unit,
+            unit: Unit::Jugg,
+            details: UnitDetails::default(),
+            position: Vec2::ZERO,
+            roster_index: Some(RosterIndex(3)),
+        };
+        let Edit::DeleteUnit { roster_index, .. } = create.inverse() else {
+            panic!("creating a unit is undone by deleting it");
+        };
+        assert_eq!(roster_index, Some(RosterIndex(3)));
+        assert_eq!(create.inverse().inverse(), create);
+    }
+
+    #[test]
+    fn inverse_of_group_reverses_the_order() {
+        let unit = Entity::from_raw(1);
+        let group = Edit::Group(vec![move_unit(unit, 0., 1.), move_unit(unit, 1., 2.)]);
+        assert_eq!(
+            group.inverse(),
+            Edit::Group(vec![move_unit(unit, 2., 1.), move_unit(unit, 1., 0.)])
+        );
+    }
+
+    #[test]
+    fn remap_replaces_units_and_anchors() {
+        let (old, new, other) = (
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+            Entity::from_raw(3),
+        );
+        let arrow = Arrow::Straight {
+            from: Vec2::ZERO,
+            to: Vec2::ONE,
+        };
+        let mut edit = Edit::Group(vec![
+            move_unit(old, 0., 1.),
+            move_unit(other, 0., 1.),
+            Edit::ChangeArrow {
+                arrow_entity: other,
+                before: arrow.clone(),
+                after: arrow.clone(),
+                anchors_before: ArrowAnchors {
+                    from: Some(old),
+                    to: Some(other),
+                },
+                anchors_after: ArrowAnchors::default(),
+            },
+        ]);
+        edit.remap(old, new);
+        assert_eq!(
+            edit,
+            Edit::Group(vec![
+                move_unit(new, 0., 1.),
+                move_unit(other, 0., 1.),
+                Edit::ChangeArrow {
+                    arrow_entity: other,
+                    before: arrow.clone(),
+                    after: arrow,
+                    anchors_before: ArrowAnchors {
+                        from: Some(new),
+                        to: Some(other),
+                    },
+                    anchors_after: ArrowAnchors::default(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn record_clears_the_redo_stack() {
+        let mut history = EditHistory::default();
+        history
+            .redo_stack
+            .push(move_unit(Entity::from_raw(1), 0., 1.));
+        history.record(move_unit(Entity::from_raw(2), 0., 1.));
+        assert!(history.redo_stack.is_empty());
+        assert_eq!(history.undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn record_drops_the_oldest_edits_beyond_the_limit() {
+        let mut history = EditHistory::default();
+        for step in 0..=MAX_HISTORY_LENGTH {
+            history.record(move_unit(
+                Entity::from_raw(1),
+                step as f32,
+                step as f32 + 1.,
+            ));
+        }
+        assert_eq!(history.undo_stack.len(), MAX_HISTORY_LENGTH);
+        assert_eq!(
+            history.undo_stack[0],
+            move_unit(Entity::from_raw(1), 1., 2.)
+        );
+    }
+}
@@ -9,8 +9,14 @@ impl Plugin for InputPlugin {
             .insert_resource(GlobalAction::input_map())
             .init_state::<InputMode>()
             .enable_state_scoped_entities::<InputMode>()
+            .add_event::<SetInputMode>()
+            .add_event::<InputModeChanged>()
             .add_systems(Update, sys_exit_bevy)
-            .add_systems(Update, sys_set_input_mode);
+            .add_systems(Update, sys_set_input_mode)
+            .add_systems(
+                Update,
+                sys_send_input_mode_changed.run_if(state_changed::<InputMode>),
+            );
     }
 }
 
@@ -20,6 +26,10 @@ pub enum GlobalAction {
     InputModeView,
     InputModePosition,
     InputModeMovement,
+    Undo,
+    Redo,
+    Delete,
+    ExtendSelection, // held while picking, to add to the selection instead of replacing it
 }
 impl GlobalAction {
     fn input_map() -> InputMap<Self> {
@@ -28,7 +38,19 @@ impl GlobalAction {
             (Self::InputModeView, KeyCode::KeyV),
             (Self::InputModePosition, KeyCode::KeyB),
             (Self::InputModeMovement, KeyCode::KeyN),
+            (Self::Delete, KeyCode::Delete),
+            (Self::Delete, KeyCode::Backspace),
         ])
+        .with(
+            Self::Undo,
+            ButtonlikeChord::modified(ModifierKey::Control, KeyCode::KeyZ),
+        )
+        .with(
+            Self::Redo,
+            ButtonlikeChord::modified(ModifierKey::Control, KeyCode::KeyZ).with(ModifierKey::Shift),
+        )
+        .with(Self::ExtendSelection, ModifierKey::Shift)
+        .with(Self::ExtendSelection, ModifierKey::Control)
     }
 }
 
@@ -40,9 +62,12 @@ fn sys_exit_bevy(action_state: Res<ActionState<GlobalAction>>, mut writer: Event
 
 fn sys_set_input_mode(
     action_state: Res<ActionState<GlobalAction>>,
+    mut er_set_input_mode: EventReader<SetInputMode>,
     mut r_next_input_mode: ResMut<NextState<InputMode>>,
 ) {
-    // TODO add buttons on screen to change the mode
+    if let Some(SetInputMode(input_mode)) = er_set_input_mode.read().last() {
+        r_next_input_mode.set(*input_mode);
+    }
     if action_state.just_pressed(&GlobalAction::InputModeView) {
         r_next_input_mode.set(InputMode::View);
     }
@@ -54,6 +79,21 @@ fn sys_set_input_mode(
     }
 }
 
+/// Switches the input mode, e.g. from the on-screen toolbar.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SetInputMode(pub InputMode);
+
+/// Tells the UI about the current input mode, so it can offer the matching tools.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct InputModeChanged(pub InputMode);
+
+fn sys_send_input_mode_changed(
+    current_input_mode: Res<State<InputMode>>,
+    mut ew_input_mode_changed: EventWriter<InputModeChanged>,
+) {
+    ew_input_mode_changed.send(InputModeChanged(**current_input_mode));
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, States, Default)]
 pub enum InputMode {
     // TODO find better names for these modes
@@ -62,3 +102,23 @@ pub enum InputMode {
     Position, // moves players and free arrows
     Movement, // draws new arrows
 }
+impl InputMode {
+    pub const ALL: [InputMode; 3] = [InputMode::View, InputMode::Position, InputMode::Movement];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InputMode::View => "View",
+            InputMode::Position => "Position",
+            InputMode::Movement => "Draw",
+        }
+    }
+
+    /// The key switching to the mode, see [`GlobalAction::input_map`].
+    pub fn shortcut(&self) -> &'static str {
+        match self {
+            InputMode::View => "V",
+            InputMode::Position => "B",
+            InputMode::Movement => "N",
+        }
+    }
+}
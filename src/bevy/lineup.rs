@@ -0,0 +1,430 @@
+use crate::bevy::{
+    arrow::{Arrow, ArrowAnchors},
+    from_meters,
+    history::{Edit, EditHistory},
+    playback::{interrupt_playback_if, BoardChangeSet},
+    scenario::{RosterIndex, ScenarioStep, ScenarioUnit, StepCapture, StepLoader},
+    to_meters,
+    unit::{
+        spawn_unit_at, Jugg, PositionalUnitType, StartPosition, Team, Unit, UnitDetails, UnitState,
+        UnitType,
+    },
+};
+use bevy::{
+    asset::{io::Reader, AssetLoadFailedEvent, AssetLoader, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypePath,
+};
+use serde::{Deserialize, Serialize};
+
+pub struct LineupPlugin;
+impl Plugin for LineupPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_asset::<Lineups>()
+            .init_asset_loader::<LineupsLoader>()
+            .init_resource::<LineupLibrary>()
+            .add_event::<LineupAction>()
+            .add_event::<LineupsChanged>()
+            .add_systems(Update, sys_apply_default_lineup)
+            .add_systems(
+                Update,
+                sys_apply_lineup_action
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<LineupAction>),
+            )
+            .add_systems(Update, interrupt_playback_if(on_event::<LineupAction>))
+            .add_systems(
+                Update,
+                sys_send_lineups_changed
+                    .run_if(resource_changed::<LineupLibrary>.or(on_event::<AssetEvent<Lineups>>)),
+            );
+    }
+}
+
+/// The lineups shipped with the app, the first one is put on the board at startup, see
+/// [`Lineup::builtin`] for when they can't be loaded.
+const DEFAULT_LINEUPS_PATH: &str = "config/default.lineups.json";
+
+/// Where the lineups defined by the user are kept in the browser.
+const USER_LINEUPS_STORAGE_KEY: &str = "jugger_strategy_tool.lineups";
+
+/// How a team lines up at the start of a round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Lineup {
+    pub name: String,
+    pub left: Vec<LineupSlot>,
+    pub right: Vec<LineupSlot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LineupSlot {
+    pub unit_type: UnitType,
+    pub slot: StartPosition,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+impl Lineup {
+    /// The runner and four numbered players for each team, in case the shipped lineups are missing.
+    fn builtin() -> Lineup {
+        let slots = [
+            (UnitType::Runner { has_jugg: false }, StartPosition::Runner),
+            (
+                UnitType::Positional(PositionalUnitType::One),
+                StartPosition::One,
+            ),
+            (
+                UnitType::Positional(PositionalUnitType::Two),
+                StartPosition::Two,
+            ),
+            (
+                UnitType::Positional(PositionalUnitType::Three),
+                StartPosition::Three,
+            ),
+            (
+                UnitType::Positional(PositionalUnitType::Four),
+                StartPosition::Four,
+            ),
+        ]
+        .map(|(unit_type, slot)| LineupSlot {
+            unit_type,
+            slot,
+            name: String::new(),
+        });
+
+        Lineup {
+            name: "Built-in".to_owned(),
+            left: slots.to_vec(),
+            right: slots.to_vec(),
+        }
+    }
+
+    /// The board with both teams lined up and the jugg in the center.
+    fn to_step(&self) -> ScenarioStep {
+        let jugg = ScenarioUnit {
+            unit: Unit::Jugg,
+            position: to_meters(Jugg::initial_position()),
+            details: UnitDetails::default(),
+        };
+        let players = [(Team::Left, &self.left), (Team::Right, &self.right)]
+            .into_iter()
+            .flat_map(|(team, slots)| {
+                slots.iter().map(move |slot| ScenarioUnit {
+                    unit: Unit::Player {
+                        team,
+                        unit_type: slot.unit_type,
+                        state: UnitState::Active,
+                    },
+                    position: to_meters(team.initial_position(slot.slot)),
+                    details: UnitDetails {
+                        name: slot.name.clone(),
+                        ..default()
+                    },
+                })
+            });
+
+        ScenarioStep {
+            units: [jugg].into_iter().chain(players).collect(),
+            ..default()
+        }
+    }
+
+    /// Lines up the players of the step, each in the free slot of its team closest to it.
+    fn from_step(name: String, step: &ScenarioStep) -> Lineup {
+        let team_slots = |team: Team| {
+            let mut free_slots = StartPosition::ALL.to_vec();
+            step.units
+                .iter()
+                .filter_map(|scenario_unit| match scenario_unit.unit {
+                    Unit::Player {
+                        team: unit_team,
+                        unit_type,
+                        ..
+                    } if unit_team == team => Some((scenario_unit, unit_type)),
+                    _ => None,
+                })
+                .filter_map(|(scenario_unit, unit_type)| {
+                    let position = from_meters(scenario_unit.position.x, scenario_unit.position.y);
+                    let distance = |slot: &StartPosition| {
+                        team.initial_position(*slot).distance_squared(position)
+                    };
+                    let Some(index) = (0..free_slots.len()).min_by(|a, b| {
+                        distance(&free_slots[*a]).total_cmp(&distance(&free_slots[*b]))
+                    }) else {
+                        warn!("Leaving out {unit_type:?} of {team:?}, all slots are taken.");
+                        return None;
+                    };
+                    Some(LineupSlot {
+                        unit_type,
+                        slot: free_slots.remove(index),
+                        name: scenario_unit.details.name.clone(),
+                    })
+                })
+                .collect()
+        };
+
+        Lineup {
+            name,
+            left: team_slots(Team::Left),
+            right: team_slots(Team::Right),
+        }
+    }
+}
+
+/// A file of lineups, see [`DEFAULT_LINEUPS_PATH`].
+#[derive(Asset, TypePath, Clone, Debug, Serialize, Deserialize)]
+pub struct Lineups {
+    pub lineups: Vec<Lineup>,
+}
+
+#[derive(Default)]
+struct LineupsLoader;
+impl AssetLoader for LineupsLoader {
+    type Asset = Lineups;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Lineups, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lineups.json"]
+    }
+}
+
+/// The lineups to choose from, those shipped with the app and those defined by the user.
+#[derive(Resource)]
+struct LineupLibrary {
+    default_lineups: Handle<Lineups>,
+    user_lineups: Vec<Lineup>, // stored in the browser
+}
+impl FromWorld for LineupLibrary {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            default_lineups: world.resource::<AssetServer>().load(DEFAULT_LINEUPS_PATH),
+            user_lineups: read_user_lineups().unwrap_or_default(),
+        }
+    }
+}
+impl LineupLibrary {
+    /// User lineups take precedence over shipped ones of the same name.
+    fn get<'a>(&'a self, name: &str, r_lineups: &'a Assets<Lineups>) -> Option<&'a Lineup> {
+        let default_lineups = r_lineups
+            .get(&self.default_lineups)
+            .map_or(&[][..], |lineups| &lineups.lineups);
+        self.user_lineups
+            .iter()
+            .chain(default_lineups)
+            .find(|lineup| lineup.name == name)
+    }
+}
+
+fn read_user_lineups() -> Option<Vec<Lineup>> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    let json = storage.get_item(USER_LINEUPS_STORAGE_KEY).ok()??;
+    serde_json::from_str::<Lineups>(&json)
+        .inspect_err(|err| error!("Failed to read the stored lineups: {err}"))
+        .ok()
+        .map(|lineups| lineups.lineups)
+}
+
+fn write_user_lineups(user_lineups: &[Lineup]) {
+    let lineups = Lineups {
+        lineups: user_lineups.to_vec(),
+    };
+    let storage = web_sys::window().and_then(|window| window.local_storage().ok().flatten());
+    let result = match (storage, serde_json::to_string(&lineups)) {
+        (Some(storage), Ok(json)) => storage
+            .set_item(USER_LINEUPS_STORAGE_KEY, &json)
+            .map_err(|err| format!("{err:?}")),
+        (None, _) => Err("no storage available".to_owned()),
+        (_, Err(err)) => Err(err.to_string()),
+    };
+    if let Err(err) = result {
+        error!("Failed to store the lineups: {err}");
+    }
+}
+
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub enum LineupAction {
+    /// Replaces the units on the board with the lineup of the given name, the arrows stay where they
+    /// are but come loose from the replaced units.
+    Apply(String),
+    /// Stores the current board as a user lineup, replacing any of the same name.
+    Save(String),
+    /// Removes the user lineup of the given name.
+    Delete(String),
+}
+
+/// Tells the UI which lineups there are to choose from.
+#[derive(Event, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineupsChanged {
+    pub default_lineups: Vec<String>,
+    pub user_lineups: Vec<String>,
+}
+
+/// Lines up the units once the lineups are loaded (or failed to), unless the board was filled in
+/// the meantime, e.g. from a shared link.
+fn sys_apply_default_lineup(
+    mut er_asset_event: EventReader<AssetEvent<Lineups>>,
+    mut er_load_failed: EventReader<AssetLoadFailedEvent<Lineups>>,
+    r_lineup_library: Res<LineupLibrary>,
+    r_lineups: Res<Assets<Lineups>>,
+    q_units: Query<(), With<Unit>>,
+    mut step_loader: StepLoader,
+) {
+    let default_lineups = &r_lineup_library.default_lineups;
+    let loaded = er_asset_event
+        .read()
+        .any(|event| event.is_loaded_with_dependencies(default_lineups));
+    let mut failed = false;
+    for event in er_load_failed.read() {
+        if event.id == default_lineups.id() {
+            warn!("Lining up the built-in lineup instead: {}", event.error);
+            failed = true;
+        }
+    }
+    if !(loaded || failed) || !q_units.is_empty() {
+        return;
+    }
+
+    let default_lineup = r_lineups
+        .get(default_lineups)
+        .and_then(|lineups| lineups.lineups.first())
+        .cloned()
+        .unwrap_or_else(Lineup::builtin);
+    step_loader.show(&default_lineup.to_step());
+}
+
+/// Puts a lineup on the board as one edit, see [`LineupAction::Apply`].
+#[derive(SystemParam)]
+struct LineupPlacer<'w, 's> {
+    q_units: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Unit,
+            &'static UnitDetails,
+            &'static Transform,
+            Option<&'static RosterIndex>,
+        ),
+    >,
+    q_arrows: Query<'w, 's, (Entity, &'static Arrow, &'static ArrowAnchors)>,
+    r_asset_server: Res<'w, AssetServer>,
+    r_history: ResMut<'w, EditHistory>,
+    commands: Commands<'w, 's>,
+}
+impl LineupPlacer<'_, '_> {
+    fn place(&mut self, lineup: &Lineup) {
+        // as when deleting units, the arrows come loose first, so undoing reattaches them to the
+        // restored units
+        let mut edits = Vec::new();
+        for (arrow_entity, arrow, anchors) in self.q_arrows.iter() {
+            if *anchors != ArrowAnchors::default() {
+                self.commands
+                    .entity(arrow_entity)
+                    .insert(ArrowAnchors::default());
+                edits.push(Edit::ChangeArrow {
+                    arrow_entity,
+                    before: arrow.clone(),
+                    after: arrow.clone(),
+                    anchors_before: *anchors,
+                    anchors_after: ArrowAnchors::default(),
+                });
+            }
+        }
+        for (unit_entity, unit, details, transform, roster_index) in self.q_units.iter() {
+            self.commands.entity(unit_entity).despawn_recursive();
+            edits.push(Edit::DeleteUnit {
+                unit_entity,
+                unit: *unit,
+                details: details.clone(),
+                position: transform.translation.xy(),
+                roster_index: roster_index.copied(),
+            });
+        }
+        // the lineup takes the places of the previous units in the roster
+        for (index, scenario_unit) in lineup.to_step().units.into_iter().enumerate() {
+            let position = from_meters(scenario_unit.position.x, scenario_unit.position.y);
+            let unit_entity = spawn_unit_at(
+                scenario_unit.unit,
+                position,
+                &mut self.commands,
+                &self.r_asset_server,
+            );
+            self.commands
+                .entity(unit_entity)
+                .insert((RosterIndex(index), scenario_unit.details.clone()));
+            edits.push(Edit::CreateUnit {
+                unit_entity,
+                unit: scenario_unit.unit,
+                details: scenario_unit.details,
+                position,
+                roster_index: Some(RosterIndex(index)),
+            });
+        }
+        if let Some(edit) = Edit::group(edits) {
+            self.r_history.record(edit);
+        }
+    }
+}
+
+fn sys_apply_lineup_action(
+    mut er_lineup_action: EventReader<LineupAction>,
+    mut r_lineup_library: ResMut<LineupLibrary>,
+    r_lineups: Res<Assets<Lineups>>,
+    step_capture: StepCapture,
+    mut lineup_placer: LineupPlacer,
+) {
+    for action in er_lineup_action.read() {
+        match action {
+            LineupAction::Apply(name) => {
+                let Some(lineup) = r_lineup_library.get(name, &r_lineups) else {
+                    warn!("There is no lineup named {name}.");
+                    continue;
+                };
+                lineup_placer.place(lineup);
+            }
+            LineupAction::Save(name) => {
+                let mut step = ScenarioStep::default();
+                step_capture.capture_into(&mut step);
+                let lineup = Lineup::from_step(name.clone(), &step);
+                let user_lineups = &mut r_lineup_library.user_lineups;
+                match user_lineups.iter_mut().find(|lineup| lineup.name == *name) {
+                    Some(existing) => *existing = lineup,
+                    None => user_lineups.push(lineup),
+                }
+                write_user_lineups(user_lineups);
+            }
+            LineupAction::Delete(name) => {
+                let user_lineups = &mut r_lineup_library.user_lineups;
+                user_lineups.retain(|lineup| lineup.name != *name);
+                write_user_lineups(user_lineups);
+            }
+        }
+    }
+}
+
+fn sys_send_lineups_changed(
+    r_lineup_library: Res<LineupLibrary>,
+    r_lineups: Res<Assets<Lineups>>,
+    mut ew_lineups_changed: EventWriter<LineupsChanged>,
+) {
+    let names = |lineups: &[Lineup]| lineups.iter().map(|lineup| lineup.name.clone()).collect();
+    ew_lineups_changed.send(LineupsChanged {
+        default_lineups: r_lineups
+            .get(&r_lineup_library.default_lineups)
+            .map_or_else(Vec::new, |lineups| names(&lineups.lineups)),
+        user_lineups: names(&r_lineup_library.user_lineups),
+    });
+}
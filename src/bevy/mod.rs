@@ -1,29 +1,83 @@
 pub mod arrow;
+pub mod arrow_handle;
+pub mod arrow_kind;
 pub mod camera;
 pub mod field;
+pub mod formation;
+pub mod history;
 pub mod input;
+pub mod lineup;
+pub mod playback;
+pub mod roster;
+pub mod scenario;
+pub mod selection;
+pub mod snapping;
+pub mod stone_clock;
+pub mod timed_event;
+pub mod timeline;
+pub mod ui_bus;
 pub mod unit;
+pub mod unit_state;
 
-use crate::{bevy::arrow::ArrowPlugin, RENDER_HEIGHT, RENDER_WIDTH};
+use crate::{
+    bevy::{arrow::ArrowPlugin, arrow_handle::ArrowHandlePlugin, arrow_kind::ArrowKindPlugin},
+    RENDER_HEIGHT, RENDER_WIDTH,
+};
 use bevy::{asset::AssetMetaCheck, prelude::*, window::WindowResolution};
 use bevy_prototype_lyon::prelude::ShapePlugin;
 use camera::CameraPlugin;
 use field::FieldPlugin;
+use formation::FormationPlugin;
+use history::HistoryPlugin;
 use input::InputPlugin;
-use leptos_bevy_canvas::prelude::{BevyQueryDuplex, LeptosBevyApp};
-use unit::{Selected, Unit, UnitPlugin};
+use leptos_bevy_canvas::prelude::{
+    BevyEventReceiver, BevyEventSender, BevyQueryDuplex, LeptosBevyApp,
+};
+use lineup::LineupPlugin;
+use playback::PlaybackPlugin;
+use roster::RosterPlugin;
+use scenario::{LoadScenario, Scenario, ScenarioPlugin, SCENARIO_URL_PREFIX};
+use selection::{Selected, SelectionPlugin};
+use snapping::SnappingPlugin;
+use stone_clock::StoneClockPlugin;
+use timed_event::TimedEventPlugin;
+use timeline::TimelinePlugin;
+use ui_bus::{UiBusPlugin, UiCommand, UiEvents};
+use unit::{Unit, UnitDetails, UnitPlugin};
+use unit_state::UnitStatePlugin;
 
 pub struct QueryDuplexes {
-    pub selected_unit_qd: BevyQueryDuplex<(Unit,), With<Selected>>,
+    pub selected_unit_qd: BevyQueryDuplex<(Unit, UnitDetails), With<Selected>>,
 }
 
-pub fn init_bevy_for_leptos(query_duplexes: QueryDuplexes) -> App {
+/// The UI talks to the board through [`UiCommand`]s, and hears back through [`UiEvents`].
+pub struct EventChannels {
+    pub ui_command_receiver: BevyEventReceiver<UiCommand>,
+    pub ui_events_sender: BevyEventSender<UiEvents>,
+}
+
+pub fn init_bevy_for_leptos(query_duplexes: QueryDuplexes, event_channels: EventChannels) -> App {
     let mut app = init_bevy();
-    app.sync_leptos_signal_with_query(query_duplexes.selected_unit_qd);
+    app.sync_leptos_signal_with_query(query_duplexes.selected_unit_qd)
+        .import_event_from_leptos(event_channels.ui_command_receiver)
+        .export_event_to_leptos(event_channels.ui_events_sender);
+
+    if let Some(scenario) = scenario_from_url() {
+        app.world_mut().send_event(LoadScenario(scenario));
+    }
 
     app
 }
 
+/// Reads a scenario shared via the URL fragment, see [`Scenario::to_url_fragment`].
+fn scenario_from_url() -> Option<Scenario> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    let fragment = hash.strip_prefix('#')?.strip_prefix(SCENARIO_URL_PREFIX)?;
+    Scenario::from_url_fragment(fragment)
+        .inspect_err(|err| error!("Failed to read scenario from URL: {err}"))
+        .ok()
+}
+
 pub fn init_bevy() -> App {
     let mut app = App::new();
     app.add_plugins(
@@ -45,11 +99,26 @@ pub fn init_bevy() -> App {
     )
     .add_plugins(MeshPickingPlugin)
     .add_plugins(ArrowPlugin)
+    .add_plugins(ArrowHandlePlugin)
+    .add_plugins(ArrowKindPlugin)
     .add_plugins(CameraPlugin)
     .add_plugins(ShapePlugin)
     .add_plugins(FieldPlugin)
     .add_plugins(UnitPlugin)
-    .add_plugins(InputPlugin);
+    .add_plugins(UnitStatePlugin)
+    .add_plugins(RosterPlugin)
+    .add_plugins(LineupPlugin)
+    .add_plugins(FormationPlugin)
+    .add_plugins(InputPlugin)
+    .add_plugins(ScenarioPlugin)
+    .add_plugins(HistoryPlugin)
+    .add_plugins(SelectionPlugin)
+    .add_plugins(SnappingPlugin)
+    .add_plugins(TimelinePlugin)
+    .add_plugins(PlaybackPlugin)
+    .add_plugins(TimedEventPlugin)
+    .add_plugins(StoneClockPlugin)
+    .add_plugins(UiBusPlugin);
 
     app
 }
@@ -64,11 +133,20 @@ pub fn from_meters(x: f32, y: f32) -> Vec2 {
 pub fn radius_from_meters(radius: f32) -> f32 {
     radius * SIZE_SCALING_FACTOR
 }
+pub fn to_meters(position: Vec2) -> Vec2 {
+    position / SIZE_SCALING_FACTOR
+}
 
 // ------------------------------
 // z-level stuff
 // ------------------------------
 const Z_LEVEL_FIELD_BACKGROUND: f32 = -2.;
+const Z_LEVEL_GRID: f32 = -1.5;
 const Z_LEVEL_ARROWS: f32 = -1.;
 const Z_LEVEL_UNITS: f32 = 0.;
 const Z_LEVEL_UNIT_SPRITES: f32 = 1.;
+const Z_LEVEL_UNIT_OVERLAYS: f32 = 1.5;
+const Z_LEVEL_ARROW_HANDLES: f32 = 2.;
+const Z_LEVEL_ALIGNMENT_GUIDES: f32 = 2.5;
+const Z_LEVEL_EVENT_FLASHES: f32 = 3.;
+const Z_LEVEL_SELECTION_BOX: f32 = 4.;
@@ -0,0 +1,373 @@
+use crate::bevy::{
+    arrow::{Arrow, ArrowAnchors},
+    arrow_kind::ArrowKind,
+    input::InputMode,
+    timeline::Timeline,
+    unit::{Unit, UNIT_RADIUS},
+    SIZE_SCALING_FACTOR,
+};
+use bevy::{ecs::schedule::SystemConfigs, prelude::*};
+
+pub struct PlaybackPlugin;
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<Playback>()
+            .add_event::<PlaybackAction>()
+            .add_event::<PlaybackChanged>()
+            .add_event::<PlaybackInterrupted>()
+            .configure_sets(Update, PlaybackInterruptSet.before(BoardChangeSet))
+            .add_systems(
+                Update,
+                sys_stop_playback_on_board_change
+                    .after(PlaybackInterruptSet)
+                    .before(BoardChangeSet)
+                    .run_if(on_event::<PlaybackInterrupted>),
+            )
+            .add_observer(on_unit_drag_started_do_stop_playback)
+            .add_systems(
+                Update,
+                sys_apply_playback_action.run_if(on_event::<PlaybackAction>),
+            )
+            .add_systems(
+                Update,
+                sys_advance_playback.after(sys_apply_playback_action),
+            )
+            .add_systems(
+                Update,
+                sys_animate_units
+                    .after(sys_advance_playback)
+                    .run_if(playback_active),
+            )
+            .add_systems(
+                Update,
+                sys_send_playback_changed
+                    .run_if(resource_changed::<Playback>.or(resource_changed::<Timeline>)),
+            );
+    }
+}
+
+/// The systems changing the board, which stop the playback first, since it would overwrite their
+/// changes otherwise, see [`interrupt_playback_if`].
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoardChangeSet;
+
+/// The systems asking for the playback to stop before the board is changed.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct PlaybackInterruptSet;
+
+/// The board is about to be changed, which ends the playback.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlaybackInterrupted;
+
+/// Stops the playback before the [`BoardChangeSet`] runs whenever the condition holds, i.e. the
+/// condition under which a system in the set changes the board.
+pub fn interrupt_playback_if<M>(condition: impl Condition<M>) -> SystemConfigs {
+    sys_interrupt_playback
+        .run_if(condition)
+        .in_set(PlaybackInterruptSet)
+}
+
+fn sys_interrupt_playback(mut ew_playback_interrupted: EventWriter<PlaybackInterrupted>) {
+    ew_playback_interrupted.send(PlaybackInterrupted);
+}
+
+const DEFAULT_SPEED: f32 = 5.;
+const MIN_SPEED: f32 = 0.1;
+const MIN_STEP_DURATION: f32 = 0.1;
+
+/// Plays back the current step, moving every unit along the movement arrows starting at it.
+///
+/// While the playback is active, i.e. not stopped, units are away from their actual positions,
+/// which are restored once it stops.
+#[derive(Resource)]
+pub struct Playback {
+    active: bool,
+    playing: bool,
+    time: f32,                 // in seconds, since the start of the step
+    speed: f32,                // of the units, in m/s
+    played_until: Option<f32>, // the time events were last played back at, None if not yet
+}
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            active: false,
+            playing: false,
+            time: 0.,
+            speed: DEFAULT_SPEED,
+            played_until: None,
+        }
+    }
+}
+
+pub fn playback_active(r_playback: Res<Playback>) -> bool {
+    r_playback.active
+}
+
+pub fn playback_inactive(r_playback: Res<Playback>) -> bool {
+    !r_playback.active
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum PlaybackAction {
+    Play,
+    Pause,
+    Stop,
+    Scrub(f32), // jumps to the given time and pauses there
+    SetSpeed(f32),
+    SetStepDuration(f32),
+}
+
+/// Tells the UI about the state of the playback.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct PlaybackChanged {
+    pub playing: bool,
+    pub time: f32,
+    pub duration: f32,
+    pub speed: f32,
+}
+
+impl Playback {
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Whether something happening at the given time did so since the events were last played
+    /// back, see [`Playback::mark_played`].
+    pub fn just_happened(&self, time: f32) -> bool {
+        let played_before = matches!(self.played_until, Some(played_until) if played_until >= time);
+        !played_before && time <= self.time
+    }
+
+    pub fn mark_played(&mut self) {
+        // avoid triggering change detection while paused
+        if self.played_until != Some(self.time) {
+            self.played_until = Some(self.time);
+        }
+    }
+}
+
+/// The actual state of a unit while it is played back, along with the path it follows.
+#[derive(Component, Clone, Debug)]
+pub struct PlaybackOrigin {
+    unit: Unit,
+    path: Vec<Vec2>, // starts at the actual position
+}
+impl PlaybackOrigin {
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.path[0]
+    }
+
+    /// The point `distance` along the path, or its end if the path is shorter.
+    fn point_at(&self, distance: f32) -> Vec2 {
+        let mut remaining = distance;
+        for segment in self.path.windows(2) {
+            let segment_length = segment[0].distance(segment[1]);
+            if segment_length >= remaining && segment_length > 0. {
+                return segment[0].lerp(segment[1], remaining / segment_length);
+            }
+            remaining -= segment_length;
+        }
+        self.path[self.path.len() - 1]
+    }
+}
+
+/// Follows the movement arrows starting at the unit, continuing with loose arrows starting within
+/// `reach` of where the previous one ended.
+///
+/// The arrows are given along with the unit their start is attached to, so units can be told apart
+/// by their entity on the board as well as by their index within a stored step.
+pub fn movement_path<U: Copy + PartialEq>(
+    unit: U,
+    position: Vec2,
+    arrows: &[(&Arrow, &ArrowKind, Option<U>)],
+    reach: f32,
+) -> Vec<Vec2> {
+    let mut used = vec![false; arrows.len()];
+    let mut path = vec![position];
+    loop {
+        let end = path[path.len() - 1];
+        let next = arrows
+            .iter()
+            .enumerate()
+            .position(|(index, (arrow, kind, from_unit))| {
+                let continues = match path.len() {
+                    1 => *from_unit == Some(unit),
+                    _ => from_unit.is_none() && arrow.start().distance(end) <= reach,
+                };
+                !used[index] && **kind == ArrowKind::Movement && continues
+            });
+        let Some(next) = next else {
+            break;
+        };
+        used[next] = true;
+        path.extend(arrows[next].0.polyline().into_iter().skip(1));
+    }
+    path
+}
+
+type UnitPlaybackQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static mut Unit,
+        Option<&'static PlaybackOrigin>,
+    ),
+    With<Unit>,
+>;
+
+fn start_playback(
+    r_playback: &mut Playback,
+    q_units: &UnitPlaybackQuery,
+    q_arrows: &Query<(&Arrow, &ArrowKind, &ArrowAnchors)>,
+    commands: &mut Commands,
+) {
+    if r_playback.active {
+        return;
+    }
+    r_playback.active = true;
+    r_playback.played_until = None;
+
+    let arrows = q_arrows
+        .iter()
+        .map(|(arrow, kind, anchors)| (arrow, kind, anchors.from))
+        .collect::<Vec<_>>();
+    for (entity, transform, unit, _) in q_units.iter() {
+        commands.entity(entity).insert(PlaybackOrigin {
+            unit: *unit,
+            path: movement_path(entity, transform.translation.xy(), &arrows, UNIT_RADIUS),
+        });
+    }
+}
+
+fn stop_playback(
+    r_playback: &mut Playback,
+    q_units: &mut UnitPlaybackQuery,
+    commands: &mut Commands,
+) {
+    r_playback.playing = false;
+    r_playback.time = 0.;
+    r_playback.played_until = None;
+    if !r_playback.active {
+        return;
+    }
+    r_playback.active = false;
+
+    for (entity, mut transform, mut unit, origin) in q_units.iter_mut() {
+        if let Some(origin) = origin {
+            transform.translation = origin.position().extend(transform.translation.z);
+            *unit = origin.unit;
+            commands.entity(entity).remove::<PlaybackOrigin>();
+        }
+    }
+}
+
+fn sys_apply_playback_action(
+    mut er_playback_action: EventReader<PlaybackAction>,
+    mut r_playback: ResMut<Playback>,
+    mut r_timeline: ResMut<Timeline>,
+    mut q_units: UnitPlaybackQuery,
+    q_arrows: Query<(&Arrow, &ArrowKind, &ArrowAnchors)>,
+    mut commands: Commands,
+) {
+    for action in er_playback_action.read() {
+        let duration = r_timeline.current_step().duration;
+        match *action {
+            PlaybackAction::Play => {
+                if r_playback.time >= duration {
+                    // replaying, the events happen once more
+                    r_playback.time = 0.;
+                    r_playback.played_until = None;
+                }
+                start_playback(&mut r_playback, &q_units, &q_arrows, &mut commands);
+                r_playback.playing = true;
+            }
+            PlaybackAction::Pause => r_playback.playing = false,
+            PlaybackAction::Stop => stop_playback(&mut r_playback, &mut q_units, &mut commands),
+            PlaybackAction::Scrub(time) => {
+                start_playback(&mut r_playback, &q_units, &q_arrows, &mut commands);
+                r_playback.playing = false;
+                r_playback.time = time.clamp(0., duration);
+            }
+            PlaybackAction::SetSpeed(speed) => r_playback.speed = speed.max(MIN_SPEED),
+            PlaybackAction::SetStepDuration(duration) => {
+                r_timeline.set_current_duration(duration.max(MIN_STEP_DURATION));
+                r_playback.time = r_playback.time.min(r_timeline.current_step().duration);
+            }
+        }
+    }
+}
+
+/// Edits end the playback, so they are made to the actual board. The units of a replaced board are
+/// gone, so their playback ends with them as well.
+fn sys_stop_playback_on_board_change(
+    mut r_playback: ResMut<Playback>,
+    mut q_units: UnitPlaybackQuery,
+    mut commands: Commands,
+) {
+    stop_playback(&mut r_playback, &mut q_units, &mut commands);
+}
+
+/// Units dragged during the playback are put back first, so they are moved from where they
+/// actually are.
+///
+/// Global observers run before the ones watching the unit, i.e. before the drag is handled.
+fn on_unit_drag_started_do_stop_playback(
+    trigger: Trigger<Pointer<DragStart>>,
+    current_input_mode: Res<State<InputMode>>,
+    mut r_playback: ResMut<Playback>,
+    mut q_units: UnitPlaybackQuery,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary
+        || **current_input_mode == InputMode::View
+        || !q_units.contains(trigger.target)
+    {
+        return;
+    }
+    stop_playback(&mut r_playback, &mut q_units, &mut commands);
+}
+
+fn sys_advance_playback(
+    time: Res<Time>,
+    mut r_playback: ResMut<Playback>,
+    r_timeline: Res<Timeline>,
+) {
+    if !r_playback.playing {
+        return;
+    }
+    let duration = r_timeline.current_step().duration;
+    r_playback.time = (r_playback.time + time.delta_secs()).min(duration);
+    if r_playback.time >= duration {
+        r_playback.playing = false;
+    }
+}
+
+fn sys_animate_units(
+    r_playback: Res<Playback>,
+    mut q_units: Query<(&mut Transform, &PlaybackOrigin), With<Unit>>,
+) {
+    let distance = r_playback.time * r_playback.speed * SIZE_SCALING_FACTOR;
+    for (mut transform, origin) in q_units.iter_mut() {
+        let position = origin.point_at(distance);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn sys_send_playback_changed(
+    r_playback: Res<Playback>,
+    r_timeline: Res<Timeline>,
+    mut ew_playback_changed: EventWriter<PlaybackChanged>,
+) {
+    ew_playback_changed.send(PlaybackChanged {
+        playing: r_playback.playing,
+        time: r_playback.time,
+        duration: r_timeline.current_step().duration,
+        speed: r_playback.speed,
+    });
+}
@@ -0,0 +1,252 @@
+use crate::bevy::{
+    arrow::{Arrow, ArrowAnchors},
+    history::{Edit, EditHistory},
+    input::GlobalAction,
+    playback::{interrupt_playback_if, BoardChangeSet},
+    scenario::RosterIndex,
+    selection::{DeleteSelected, Selected, SelectionRegistry},
+    unit::{spawn_unit_at, Team, Unit, UnitDetails},
+};
+use bevy::prelude::*;
+use leafwing_input_manager::common_conditions::action_just_pressed;
+
+pub struct RosterPlugin;
+impl Plugin for RosterPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_event::<SpawnUnit>()
+            .add_event::<EditSelectedUnit>()
+            .add_event::<RosterChanged>()
+            .add_systems(
+                Update,
+                sys_spawn_unit
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<SpawnUnit>),
+            )
+            .add_systems(
+                Update,
+                sys_delete_selected_unit
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<DeleteSelected>),
+            )
+            .add_systems(
+                Update,
+                sys_edit_selected_unit
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<EditSelectedUnit>),
+            )
+            .add_systems(
+                Update,
+                interrupt_playback_if(
+                    on_event::<SpawnUnit>
+                        .or(on_event::<EditSelectedUnit>)
+                        .or(on_event::<DeleteSelected>)
+                        // the event is sent alongside, it may be read only after the board change
+                        .or(action_just_pressed(GlobalAction::Delete)),
+                ),
+            )
+            .add_systems(Update, sys_send_roster_changed);
+    }
+}
+
+/// The number of players a team has on the field, as per the rules.
+pub const MAX_PLAYERS_PER_TEAM: usize = 5;
+
+/// Adds a unit to the board, e.g. dropped there from the palette.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct SpawnUnit {
+    pub unit: Unit,
+    /// Position within the canvas, in logical pixels. The unit is placed at the center of the view
+    /// if it's missing.
+    pub screen_position: Option<Vec2>,
+}
+
+/// Changes the selected unit, e.g. from the unit editor.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct EditSelectedUnit {
+    pub unit: Unit,
+    pub details: UnitDetails,
+}
+
+/// Tells the UI how many units are on the board.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RosterChanged {
+    pub left: usize,
+    pub right: usize,
+    pub juggs: usize,
+}
+impl RosterChanged {
+    fn count<'a>(units: impl Iterator<Item = &'a Unit>) -> RosterChanged {
+        let mut roster = RosterChanged::default();
+        units.for_each(|unit| roster.add(unit));
+        roster
+    }
+
+    pub fn players(&self, team: Team) -> usize {
+        match team {
+            Team::Left => self.left,
+            Team::Right => self.right,
+        }
+    }
+
+    fn add(&mut self, unit: &Unit) {
+        match unit {
+            Unit::Jugg => self.juggs += 1,
+            Unit::Player {
+                team: Team::Left, ..
+            } => self.left += 1,
+            Unit::Player {
+                team: Team::Right, ..
+            } => self.right += 1,
+        }
+    }
+
+    /// Whether the unit can be added without breaking the rules.
+    pub fn has_room_for(&self, unit: &Unit) -> bool {
+        match unit {
+            Unit::Jugg => self.juggs == 0,
+            Unit::Player { team, .. } => self.players(*team) < MAX_PLAYERS_PER_TEAM,
+        }
+    }
+}
+
+fn sys_spawn_unit(
+    mut er_spawn_unit: EventReader<SpawnUnit>,
+    q_units: Query<&Unit>,
+    q_camera: Single<(&Camera, &GlobalTransform)>,
+    r_asset_server: Res<AssetServer>,
+    mut r_history: ResMut<EditHistory>,
+    mut commands: Commands,
+) {
+    let (camera, camera_transform) = *q_camera;
+    let mut roster = RosterChanged::count(q_units.iter());
+    for SpawnUnit {
+        unit,
+        screen_position,
+    } in er_spawn_unit.read()
+    {
+        if !roster.has_room_for(unit) {
+            warn!("Not adding {unit:?}, there is no room for it on the field.");
+            continue;
+        }
+        let position = match screen_position {
+            Some(screen_position) => {
+                match camera.viewport_to_world_2d(camera_transform, *screen_position) {
+                    Ok(position) => position,
+                    Err(err) => {
+                        warn!("Not adding {unit:?} outside of the view: {err:?}");
+                        continue;
+                    }
+                }
+            }
+            None => camera_transform.translation().xy(),
+        };
+
+        let unit_entity = spawn_unit_at(*unit, position, &mut commands, &r_asset_server);
+        r_history.record(Edit::CreateUnit {
+            unit_entity,
+            unit: *unit,
+            details: UnitDetails::default(),
+            position,
+            roster_index: None, // assigned once spawned
+        });
+        roster.add(unit);
+    }
+}
+
+fn sys_delete_selected_unit(
+    mut er_delete_selected: EventReader<DeleteSelected>,
+    r_selection_registry: Res<SelectionRegistry>,
+    q_units: Query<(&Unit, &UnitDetails, &Transform, Option<&RosterIndex>)>,
+    q_arrows: Query<(Entity, &Arrow, &ArrowAnchors)>,
+    mut r_history: ResMut<EditHistory>,
+    mut commands: Commands,
+) {
+    er_delete_selected.clear();
+    let mut deletions = Vec::new();
+    for selected in r_selection_registry.selected() {
+        if let Ok((unit, details, transform, roster_index)) = q_units.get(selected) {
+            commands.entity(selected).despawn_recursive();
+            deletions.push(Edit::DeleteUnit {
+                unit_entity: selected,
+                unit: *unit,
+                details: details.clone(),
+                position: transform.translation.xy(),
+                roster_index: roster_index.copied(),
+            });
+        }
+    }
+
+    // arrows come loose from the deleted units, undoing attaches them to the restored ones again,
+    // which is why the deletions have to come last
+    let is_kept =
+        |unit: &Entity| !r_selection_registry.is_selected(*unit) || !q_units.contains(*unit);
+    let mut detachments = Vec::new();
+    for (arrow_entity, arrow, anchors) in q_arrows.iter() {
+        let detached = ArrowAnchors {
+            from: anchors.from.filter(is_kept),
+            to: anchors.to.filter(is_kept),
+        };
+        if detached != *anchors {
+            detachments.push(Edit::ChangeArrow {
+                arrow_entity,
+                before: arrow.clone(),
+                after: arrow.clone(),
+                anchors_before: *anchors,
+                anchors_after: detached,
+            });
+        }
+    }
+    detachments.extend(deletions);
+    if let Some(edit) = Edit::group(detachments) {
+        r_history.record(edit);
+    }
+}
+
+fn sys_edit_selected_unit(
+    mut er_edit_selected_unit: EventReader<EditSelectedUnit>,
+    mut q_selected: Query<(Entity, &mut Unit, &mut UnitDetails), With<Selected>>,
+    q_others: Query<&Unit, Without<Selected>>,
+    mut r_history: ResMut<EditHistory>,
+) {
+    let Some(EditSelectedUnit {
+        unit: new_unit,
+        details: new_details,
+    }) = er_edit_selected_unit.read().last()
+    else {
+        return;
+    };
+    let Ok((entity, mut unit, mut details)) = q_selected.get_single_mut() else {
+        warn!("Only a single selected unit can be edited.");
+        return;
+    };
+    // e.g. moving a player to a team which is full already
+    if !RosterChanged::count(q_others.iter()).has_room_for(new_unit) {
+        warn!("There is no room for {new_unit:?} on the field.");
+        return;
+    }
+    if *unit == *new_unit && *details == *new_details {
+        return;
+    }
+
+    r_history.record(Edit::ChangeUnit {
+        unit: entity,
+        before: *unit,
+        after: *new_unit,
+        details_before: details.clone(),
+        details_after: new_details.clone(),
+    });
+    *unit = *new_unit;
+    *details = new_details.clone();
+}
+
+fn sys_send_roster_changed(
+    q_units: Query<&Unit>,
+    mut previous: Local<Option<RosterChanged>>,
+    mut ew_roster_changed: EventWriter<RosterChanged>,
+) {
+    let roster = RosterChanged::count(q_units.iter());
+    if *previous != Some(roster) {
+        *previous = Some(roster);
+        ew_roster_changed.send(roster);
+    }
+}
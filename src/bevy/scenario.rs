@@ -0,0 +1,543 @@
+use crate::bevy::{
+    arrow::{spawn_arrow, Arrow, ArrowAnchors},
+    arrow_kind::ArrowKind,
+    field::FieldVariant,
+    from_meters,
+    playback::{interrupt_playback_if, movement_path, BoardChangeSet, PlaybackOrigin},
+    timed_event::TimedEvent,
+    timeline::Timeline,
+    to_meters,
+    unit::{spawn_unit_at, Unit, UnitDetails, UNIT_RADIUS},
+    SIZE_SCALING_FACTOR,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+pub struct ScenarioPlugin;
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<ScenarioMetadata>()
+            .add_event::<LoadScenario>()
+            .add_event::<RequestScenarioExport>()
+            .add_event::<ScenarioExported>()
+            .add_systems(
+                Update,
+                sys_load_scenario
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<LoadScenario>),
+            )
+            .add_systems(Update, interrupt_playback_if(on_event::<LoadScenario>))
+            .add_systems(Update, sys_assign_roster_indices)
+            .add_systems(
+                Update,
+                sys_export_scenario.run_if(on_event::<RequestScenarioExport>),
+            );
+    }
+}
+
+/// The document describing everything on the board.
+///
+/// All positions are stored in meters, so documents are independent of the rendering scale.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub metadata: ScenarioMetadata,
+    pub field: FieldVariant,
+    /// The board at each step of the play, in order.
+    pub steps: Vec<ScenarioStep>,
+}
+
+/// The board at a single step of the play.
+///
+/// Units are listed in the same order in every step, so they can be followed from step to step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioStep {
+    pub units: Vec<ScenarioUnit>,
+    pub arrows: Vec<ScenarioArrow>,
+    /// How long playing back the step takes, in seconds.
+    #[serde(default = "default_step_duration")]
+    pub duration: f32,
+    /// Sorted by time.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<TimedEvent>,
+}
+impl Default for ScenarioStep {
+    fn default() -> Self {
+        Self {
+            units: Vec::new(),
+            arrows: Vec::new(),
+            duration: default_step_duration(),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl ScenarioStep {
+    /// The units at the ends of their movement arrows, i.e. where the step takes them.
+    pub fn units_after_movement(&self) -> Vec<ScenarioUnit> {
+        let arrows = self
+            .arrows
+            .iter()
+            .map(|scenario_arrow| {
+                (
+                    &scenario_arrow.arrow,
+                    &scenario_arrow.kind,
+                    scenario_arrow.from_unit,
+                )
+            })
+            .collect::<Vec<_>>();
+        self.units
+            .iter()
+            .enumerate()
+            .map(|(index, scenario_unit)| {
+                let path = movement_path(
+                    index,
+                    scenario_unit.position,
+                    &arrows,
+                    UNIT_RADIUS / SIZE_SCALING_FACTOR, // the step is stored in meters
+                );
+                ScenarioUnit {
+                    position: path[path.len() - 1],
+                    ..scenario_unit.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// Removes the units at the given (sorted) roster indices along with their events, and detaches
+    /// the arrows from them. The units after them move up, so they keep lining up with other steps.
+    pub fn remove_units(&mut self, removed: &[usize]) {
+        let new_index = |index: usize| {
+            RosterIndex(index)
+                .after_removal(removed)
+                .map(|roster_index| roster_index.0)
+        };
+        self.units = std::mem::take(&mut self.units)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !removed.contains(index))
+            .map(|(_, scenario_unit)| scenario_unit)
+            .collect();
+        for scenario_arrow in &mut self.arrows {
+            scenario_arrow.from_unit = scenario_arrow.from_unit.and_then(new_index);
+            scenario_arrow.to_unit = scenario_arrow.to_unit.and_then(new_index);
+        }
+        self.events.retain_mut(|event| match new_index(event.unit) {
+            Some(index) => {
+                event.unit = index;
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+fn default_step_duration() -> f32 {
+    5.
+}
+
+/// Marks the scenario within the URL fragment, i.e. `#scenario=<encoded scenario>`.
+pub const SCENARIO_URL_PREFIX: &str = "scenario=";
+
+/// Upper bound for decompressed scenarios, to protect against maliciously crafted links.
+const MAX_DECODED_SIZE: usize = 1 << 20;
+
+impl Scenario {
+    /// Encodes the scenario as compressed, URL-safe text suitable for the URL fragment.
+    pub fn to_url_fragment(&self) -> Result<String, String> {
+        let json = serde_json::to_vec(self).map_err(|err| err.to_string())?;
+        let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+        Ok(URL_SAFE_NO_PAD.encode(compressed))
+    }
+
+    pub fn from_url_fragment(fragment: &str) -> Result<Scenario, String> {
+        let compressed = URL_SAFE_NO_PAD
+            .decode(fragment)
+            .map_err(|err| err.to_string())?;
+        let json =
+            miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_SIZE)
+                .map_err(|err| err.to_string())?;
+        serde_json::from_slice(&json).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScenarioMetadata {
+    pub name: String,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioUnit {
+    pub unit: Unit,
+    pub position: Vec2,
+    #[serde(default, skip_serializing_if = "UnitDetails::is_empty")]
+    pub details: UnitDetails,
+}
+
+/// An arrow, whose ends may be attached to units, given as indices into [`ScenarioStep::units`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScenarioArrow {
+    #[serde(flatten)]
+    pub arrow: Arrow,
+    #[serde(default)]
+    pub kind: ArrowKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_unit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_unit: Option<usize>,
+}
+
+/// Replaces all units and arrows on the board with the contents of the scenario.
+#[derive(Event, Clone, Debug)]
+pub struct LoadScenario(pub Scenario);
+
+/// Asks for the current board to be sent out as a [`ScenarioExported`] event.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct RequestScenarioExport(pub ExportTarget);
+
+#[derive(Event, Clone, Debug)]
+pub struct ScenarioExported {
+    pub scenario: Scenario,
+    pub target: ExportTarget,
+}
+
+/// What the exported scenario is meant for, so the receiver knows how to handle it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportTarget {
+    File,
+    Link,
+}
+
+/// Position of a unit within [`ScenarioStep::units`], which is the same for every step.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RosterIndex(pub usize);
+impl RosterIndex {
+    /// The index once the units at the given (sorted) indices left the roster, `None` if this is
+    /// one of them.
+    pub fn after_removal(self, removed: &[usize]) -> Option<RosterIndex> {
+        (!removed.contains(&self.0))
+            .then(|| RosterIndex(self.0 - removed.partition_point(|index| *index < self.0)))
+    }
+}
+
+type StepUnitQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Unit,
+        &'static UnitDetails,
+        &'static Transform,
+        Option<&'static RosterIndex>,
+        Option<&'static PlaybackOrigin>,
+    ),
+>;
+
+/// Reads the board, i.e. the current step, from the world.
+#[derive(SystemParam)]
+pub struct StepCapture<'w, 's> {
+    q_units: StepUnitQuery<'w, 's>,
+    q_arrows: Query<'w, 's, (&'static Arrow, &'static ArrowKind, &'static ArrowAnchors)>,
+}
+impl StepCapture<'_, '_> {
+    /// Replaces the units and arrows of the step with those on the board.
+    ///
+    /// Units deleted from the board leave gaps in the roster, which are closed, also in the events
+    /// of the step. Returns the roster indices of the deleted units, so other steps can follow suit,
+    /// see [`ScenarioStep::remove_units`].
+    pub fn capture_into(&self, step: &mut ScenarioStep) -> Vec<usize> {
+        // units without an index (e.g. added since the step was shown) go last
+        let mut units = self.q_units.iter().collect::<Vec<_>>();
+        units.sort_by_key(|(_, _, _, _, roster_index, _)| {
+            roster_index.map_or(usize::MAX, |index| index.0)
+        });
+        let roster_len = units
+            .iter()
+            .filter_map(|(_, _, _, _, roster_index, _)| roster_index.map(|index| index.0 + 1))
+            .chain(step.events.iter().map(|event| event.unit + 1))
+            .fold(step.units.len(), usize::max);
+        let removed = (0..roster_len)
+            .filter(|index| {
+                !units.iter().any(|(_, _, _, _, roster_index, _)| {
+                    *roster_index == Some(&RosterIndex(*index))
+                })
+            })
+            .collect::<Vec<_>>();
+        step.remove_units(&removed);
+        let unit_index = |anchor: Option<Entity>| {
+            anchor.and_then(|entity| units.iter().position(|(unit, ..)| *unit == entity))
+        };
+
+        step.units = units
+            .iter()
+            .map(
+                |(_, unit, details, transform, _, playback_origin)| match playback_origin {
+                    // units being played back are only away from their actual state temporarily
+                    Some(origin) => ScenarioUnit {
+                        unit: origin.unit(),
+                        position: to_meters(origin.position()),
+                        details: (*details).clone(),
+                    },
+                    None => ScenarioUnit {
+                        unit: **unit,
+                        position: to_meters(transform.translation.xy()),
+                        details: (*details).clone(),
+                    },
+                },
+            )
+            .collect();
+        step.arrows = self
+            .q_arrows
+            .iter()
+            .map(|(arrow, kind, anchors)| ScenarioArrow {
+                arrow: arrow.map_points(to_meters),
+                kind: *kind,
+                from_unit: unit_index(anchors.from),
+                to_unit: unit_index(anchors.to),
+            })
+            .collect();
+        removed
+    }
+}
+
+/// Reads the whole scenario from the world, see [`ScenarioCapture::capture`].
+#[derive(SystemParam)]
+pub struct ScenarioCapture<'w, 's> {
+    step_capture: StepCapture<'w, 's>,
+    r_timeline: Res<'w, Timeline>,
+    r_field: Res<'w, FieldVariant>,
+    r_metadata: Res<'w, ScenarioMetadata>,
+}
+impl ScenarioCapture<'_, '_> {
+    pub fn capture(&self) -> Scenario {
+        // the timeline only holds the current step as it was when it was shown
+        let mut timeline = self.r_timeline.clone();
+        timeline.capture_current(&self.step_capture);
+
+        Scenario {
+            metadata: self.r_metadata.clone(),
+            field: *self.r_field,
+            steps: timeline.steps().to_vec(),
+        }
+    }
+}
+
+/// Everything a step consists of.
+type BoardQuery<'w, 's> = Query<'w, 's, Entity, Or<(With<Unit>, With<Arrow>)>>;
+
+/// Replaces all units and arrows on the board with those of a step.
+#[derive(SystemParam)]
+pub struct StepLoader<'w, 's> {
+    q_board: BoardQuery<'w, 's>,
+    r_asset_server: Res<'w, AssetServer>,
+    commands: Commands<'w, 's>,
+}
+impl StepLoader<'_, '_> {
+    pub fn show(&mut self, step: &ScenarioStep) {
+        self.q_board.iter().for_each(|entity| {
+            self.commands.entity(entity).despawn_recursive();
+        });
+
+        let unit_entities = step
+            .units
+            .iter()
+            .enumerate()
+            .map(|(index, scenario_unit)| {
+                let entity = spawn_unit_at(
+                    scenario_unit.unit,
+                    from_meters(scenario_unit.position.x, scenario_unit.position.y),
+                    &mut self.commands,
+                    &self.r_asset_server,
+                );
+                self.commands
+                    .entity(entity)
+                    .insert((RosterIndex(index), scenario_unit.details.clone()));
+                entity
+            })
+            .collect::<Vec<_>>();
+        step.arrows.iter().for_each(|scenario_arrow| {
+            let unit_entity =
+                |index: Option<usize>| index.and_then(|i| unit_entities.get(i).copied());
+            spawn_arrow(
+                scenario_arrow
+                    .arrow
+                    .map_points(|point| from_meters(point.x, point.y)),
+                scenario_arrow.kind,
+                ArrowAnchors {
+                    from: unit_entity(scenario_arrow.from_unit),
+                    to: unit_entity(scenario_arrow.to_unit),
+                },
+                &mut self.commands,
+            );
+        });
+    }
+}
+
+/// Appends units placed on the board to the roster, e.g. the ones spawned at startup.
+///
+/// The roster spans all steps, so new units don't take the place of units shown in other steps.
+fn sys_assign_roster_indices(
+    q_unindexed: Query<Entity, (With<Unit>, Without<RosterIndex>)>,
+    q_indices: Query<&RosterIndex>,
+    r_timeline: Res<Timeline>,
+    mut commands: Commands,
+) {
+    if q_unindexed.is_empty() {
+        return;
+    }
+    let step_lengths = r_timeline.steps().iter().map(|step| step.units.len());
+    let first_index = q_indices
+        .iter()
+        .map(|index| index.0 + 1)
+        .chain(step_lengths)
+        .max()
+        .unwrap_or(0);
+    for (index, entity) in (first_index..).zip(q_unindexed.iter()) {
+        commands.entity(entity).insert(RosterIndex(index));
+    }
+}
+
+fn sys_load_scenario(
+    mut er_load_scenario: EventReader<LoadScenario>,
+    mut r_metadata: ResMut<ScenarioMetadata>,
+    mut r_field: ResMut<FieldVariant>,
+    mut r_timeline: ResMut<Timeline>,
+    mut step_loader: StepLoader,
+) {
+    // only the latest request matters, since each load replaces the whole board
+    let Some(LoadScenario(scenario)) = er_load_scenario.read().last() else {
+        return;
+    };
+
+    *r_metadata = scenario.metadata.clone();
+    *r_field = scenario.field;
+    *r_timeline = Timeline::new(scenario.steps.clone());
+    step_loader.show(r_timeline.current_step());
+}
+
+fn sys_export_scenario(
+    mut er_request_export: EventReader<RequestScenarioExport>,
+    scenario_capture: ScenarioCapture,
+    mut ew_scenario_exported: EventWriter<ScenarioExported>,
+) {
+    er_request_export
+        .read()
+        .for_each(|RequestScenarioExport(target)| {
+            ew_scenario_exported.send(ScenarioExported {
+                scenario: scenario_capture.capture(),
+                target: *target,
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bevy::timed_event::TimedEventKind;
+
+    fn step_with_units(count: usize) -> ScenarioStep {
+        ScenarioStep {
+            units: (0..count)
+                .map(|index| ScenarioUnit {
+                    unit: Unit::Jugg,
+                    position: Vec2::new(index as f32, 0.),
+                    details: UnitDetails::default(),
+                })
+                .collect(),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn url_fragment_round_trip() {
+        let mut step = step_with_units(2);
+        step.events.push(TimedEvent {
+            time: 1.5,
+            unit: 1,
+            kind: TimedEventKind::Hit { downtime: 5 },
+        });
+        let scenario = Scenario {
+            metadata: ScenarioMetadata {
+                name: "Test".to_owned(),
+                description: "Two steps".to_owned(),
+            },
+            field: FieldVariant::default(),
+            steps: vec![step, step_with_units(1)],
+        };
+
+        let fragment = scenario.to_url_fragment().unwrap();
+        assert!(fragment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        let decoded = Scenario::from_url_fragment(&fragment).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(&scenario).unwrap()
+        );
+    }
+
+    #[test]
+    fn url_fragment_rejects_invalid_input() {
+        assert!(Scenario::from_url_fragment("not a scenario!").is_err());
+        let uncompressed = URL_SAFE_NO_PAD.encode(b"{}");
+        assert!(Scenario::from_url_fragment(&uncompressed).is_err());
+        // e.g. a zip bomb
+        let oversized = miniz_oxide::deflate::compress_to_vec(&vec![b' '; MAX_DECODED_SIZE + 1], 9);
+        assert!(Scenario::from_url_fragment(&URL_SAFE_NO_PAD.encode(oversized)).is_err());
+    }
+
+    #[test]
+    fn roster_index_after_removal() {
+        let removed = [1, 3];
+        assert_eq!(RosterIndex(0).after_removal(&removed), Some(RosterIndex(0)));
+        assert_eq!(RosterIndex(1).after_removal(&removed), None);
+        assert_eq!(RosterIndex(2).after_removal(&removed), Some(RosterIndex(1)));
+        assert_eq!(RosterIndex(4).after_removal(&removed), Some(RosterIndex(2)));
+    }
+
+    #[test]
+    fn remove_units_moves_up_the_units_after_them() {
+        let mut step = step_with_units(4);
+        step.remove_units(&[1]);
+        let positions = step
+            .units
+            .iter()
+            .map(|unit| unit.position.x)
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![0., 2., 3.]);
+    }
+
+    #[test]
+    fn remove_units_remaps_arrows_and_events() {
+        let mut step = step_with_units(3);
+        step.arrows = vec![ScenarioArrow {
+            arrow: Arrow::Straight {
+                from: Vec2::ZERO,
+                to: Vec2::ONE,
+            },
+            kind: ArrowKind::default(),
+            from_unit: Some(2),
+            to_unit: Some(1),
+        }];
+        step.events = [0, 1, 2]
+            .into_iter()
+            .map(|unit| TimedEvent {
+                time: unit as f32,
+                unit,
+                kind: TimedEventKind::DropJugg,
+            })
+            .collect();
+
+        step.remove_units(&[1]);
+
+        assert_eq!(step.arrows[0].from_unit, Some(1));
+        assert_eq!(step.arrows[0].to_unit, None);
+        let events = step
+            .events
+            .iter()
+            .map(|event| (event.time, event.unit))
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![(0., 0), (2., 1)]);
+    }
+}
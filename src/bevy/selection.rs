@@ -0,0 +1,282 @@
+use crate::bevy::{
+    arrow::Arrow,
+    camera::ZoomState,
+    input::{GlobalAction, InputMode},
+    unit::Unit,
+    Z_LEVEL_SELECTION_BOX,
+};
+use bevy::{color::palettes::css::*, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+use leafwing_input_manager::prelude::*;
+
+pub struct SelectionPlugin;
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<SelectionRegistry>()
+            .add_event::<DeleteSelected>()
+            .add_event::<SelectionChanged>()
+            .add_systems(
+                Update,
+                sys_sync_selection_state.run_if(resource_changed::<SelectionRegistry>),
+            )
+            .add_systems(Update, sys_forget_removed_selectables)
+            .add_systems(Update, sys_send_delete_selected)
+            .add_systems(
+                Update,
+                sys_send_selection_changed.run_if(resource_changed::<SelectionRegistry>),
+            );
+    }
+}
+
+/// Keeps track of the selected board elements, i.e. units and arrows.
+#[derive(Resource, Default)]
+pub struct SelectionRegistry {
+    selected: Vec<Entity>, // in the order they were selected
+}
+impl SelectionRegistry {
+    /// Selects only the given entity.
+    pub fn select(&mut self, entity: Entity) {
+        self.selected = vec![entity];
+    }
+
+    /// Adds the entity to the selection, or removes it if it is already selected.
+    pub fn toggle(&mut self, entity: Entity) {
+        match self
+            .selected
+            .iter()
+            .position(|selected| *selected == entity)
+        {
+            Some(index) => {
+                self.selected.remove(index);
+            }
+            None => self.selected.push(entity),
+        }
+    }
+
+    /// Adds the entities to the selection, keeping those already selected.
+    pub fn extend(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        for entity in entities {
+            if !self.is_selected(entity) {
+                self.selected.push(entity);
+            }
+        }
+    }
+
+    /// Selects exactly the given entities.
+    pub fn replace(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        self.selected.clear();
+        self.extend(entities);
+    }
+
+    pub fn clear(&mut self) {
+        self.selected.clear();
+    }
+
+    pub fn is_selected(&self, entity: Entity) -> bool {
+        self.selected.contains(&entity)
+    }
+
+    pub fn selected(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.selected.iter().copied()
+    }
+}
+
+/// Marks board elements which can be selected.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct Selectable;
+
+#[derive(Component, Clone, Debug)]
+pub struct Selected;
+
+/// Asks for the selected board elements to be removed from the board.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct DeleteSelected;
+
+/// Tells the UI what kind of board element is selected.
+#[derive(Event, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionChanged {
+    #[default]
+    Nothing,
+    Unit,
+    Arrow,
+    /// Several elements, of any kind.
+    Group(usize),
+}
+
+fn sys_sync_selection_state(
+    r_selection_registry: Res<SelectionRegistry>,
+    q_selectable: Query<Entity, With<Selectable>>,
+    mut commands: Commands,
+) {
+    q_selectable.iter().for_each(|entity| {
+        commands.entity(entity).remove::<Selected>();
+    });
+
+    for selected_entity in r_selection_registry.selected() {
+        if let Ok(entity) = q_selectable.get(selected_entity) {
+            commands.entity(entity).insert(Selected);
+        } else {
+            error!("{selected_entity} is selected, but is not a selectable entity.")
+        }
+    }
+}
+
+fn sys_forget_removed_selectables(
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+    mut q_removed: RemovedComponents<Selectable>,
+) {
+    q_removed.read().for_each(|entity| {
+        if r_selection_registry.is_selected(entity) {
+            r_selection_registry.toggle(entity);
+        }
+    });
+}
+
+fn sys_send_delete_selected(
+    action_state: Res<ActionState<GlobalAction>>,
+    mut ew_delete_selected: EventWriter<DeleteSelected>,
+) {
+    if action_state.just_pressed(&GlobalAction::Delete) {
+        ew_delete_selected.send(DeleteSelected);
+    }
+}
+
+fn sys_send_selection_changed(
+    r_selection_registry: Res<SelectionRegistry>,
+    q_units: Query<(), With<Unit>>,
+    q_arrows: Query<(), With<Arrow>>,
+    mut ew_selection_changed: EventWriter<SelectionChanged>,
+) {
+    let selection = match r_selection_registry.selected[..] {
+        [] => SelectionChanged::Nothing,
+        [entity] if q_units.contains(entity) => SelectionChanged::Unit,
+        [entity] if q_arrows.contains(entity) => SelectionChanged::Arrow,
+        [_] => SelectionChanged::Nothing,
+        ref selected => SelectionChanged::Group(selected.len()),
+    };
+    ew_selection_changed.send(selection);
+}
+
+/// Picks the grabbed element, or toggles it while [`GlobalAction::ExtendSelection`] is held.
+///
+/// Grabbing an element of the selection keeps the others, so they can be moved together.
+pub fn select_grabbed(
+    entity: Entity,
+    action_state: &ActionState<GlobalAction>,
+    r_selection_registry: &mut SelectionRegistry,
+) {
+    if action_state.pressed(&GlobalAction::ExtendSelection) {
+        r_selection_registry.toggle(entity);
+    } else if !r_selection_registry.is_selected(entity) {
+        r_selection_registry.select(entity);
+    }
+}
+
+// ------------------------------
+// box selection
+// ------------------------------
+
+/// The rubber band dragged across the field, the units inside it are selected when it is dropped.
+#[derive(Component)]
+pub struct SelectionBox {
+    from: Vec2,
+    to: Vec2,
+}
+impl SelectionBox {
+    fn rect(&self) -> Rect {
+        Rect::from_corners(self.from, self.to)
+    }
+
+    fn path(&self) -> Path {
+        let rect = self.rect();
+        GeometryBuilder::build_as(&shapes::Rectangle {
+            extents: rect.size(),
+            origin: RectangleOrigin::CustomCenter(rect.center()),
+            ..default()
+        })
+    }
+}
+
+/// Pressing on the empty field lets go of the selection, unless it is being extended.
+pub fn on_field_pressed_do_clear_selection(
+    trigger: Trigger<Pointer<Down>>,
+    current_input_mode: Res<State<InputMode>>,
+    action_state: Res<ActionState<GlobalAction>>,
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+) {
+    if trigger.button != PointerButton::Primary || **current_input_mode != InputMode::Position {
+        return;
+    }
+    if !action_state.pressed(&GlobalAction::ExtendSelection) {
+        r_selection_registry.clear();
+    }
+}
+
+pub fn on_field_drag_started_do_start_box(
+    trigger: Trigger<Pointer<DragStart>>,
+    current_input_mode: Res<State<InputMode>>,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary || **current_input_mode != InputMode::Position {
+        return;
+    }
+    let Some(position) = trigger.hit.position else {
+        return;
+    };
+    let selection_box = SelectionBox {
+        from: position.xy(),
+        to: position.xy(),
+    };
+    commands.spawn((
+        ShapeBundle {
+            path: selection_box.path(),
+            transform: Transform::from_xyz(0., 0., Z_LEVEL_SELECTION_BOX),
+            ..default()
+        },
+        Fill::color(WHITE.with_alpha(0.2)),
+        Stroke::new(WHITE, 3.),
+        PickingBehavior::IGNORE,
+        selection_box,
+        StateScoped(InputMode::Position),
+    ));
+}
+
+pub fn on_field_dragged_do_resize_box(
+    trigger: Trigger<Pointer<Drag>>,
+    mut q_boxes: Query<(&mut SelectionBox, &mut Path)>,
+    r_zoom_state: Res<ZoomState>,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let mut delta = trigger.delta;
+    delta.y *= -1.;
+    delta *= r_zoom_state.current_zoom_factor;
+    for (mut selection_box, mut path) in q_boxes.iter_mut() {
+        selection_box.to += delta;
+        *path = selection_box.path();
+    }
+}
+
+pub fn on_field_drag_ended_do_select_box(
+    trigger: Trigger<Pointer<DragEnd>>,
+    q_boxes: Query<(Entity, &SelectionBox)>,
+    q_units: Query<(Entity, &Transform), With<Unit>>,
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    for (entity, selection_box) in q_boxes.iter() {
+        let rect = selection_box.rect();
+        // the selection was already cleared when pressing, unless it is being extended
+        r_selection_registry.extend(
+            q_units
+                .iter()
+                .filter(|(_, transform)| rect.contains(transform.translation.xy()))
+                .map(|(unit, _)| unit),
+        );
+        commands.entity(entity).despawn();
+    }
+}
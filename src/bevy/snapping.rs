@@ -0,0 +1,317 @@
+use crate::bevy::{
+    field::FIELD_HALF_EXTENTS, SIZE_SCALING_FACTOR, Z_LEVEL_ALIGNMENT_GUIDES, Z_LEVEL_GRID,
+};
+use bevy::{color::palettes::css::*, ecs::system::SystemParam, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+pub struct SnappingPlugin;
+impl Plugin for SnappingPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<SnapSettings>()
+            .init_resource::<AlignmentGuides>()
+            .add_event::<SetSnapSettings>()
+            .add_event::<SnapSettingsChanged>()
+            .add_systems(
+                Update,
+                sys_set_snap_settings.run_if(on_event::<SetSnapSettings>),
+            )
+            .add_systems(
+                Update,
+                (sys_update_grid_overlay, sys_send_snap_settings_changed)
+                    .run_if(resource_changed::<SnapSettings>),
+            )
+            .add_systems(
+                Update,
+                sys_update_alignment_guides.run_if(resource_changed::<AlignmentGuides>),
+            );
+    }
+}
+
+/// How close a dragged point has to come to being in line with another one to snap to it, in
+/// logical pixels.
+const GUIDE_TOLERANCE: f32 = 8.;
+
+/// How units and arrow ends are lined up while being dragged.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnapSettings {
+    /// Shows a grid and snaps onto its intersections.
+    pub grid: Option<GridSpacing>,
+    /// Snaps into line with the other units, showing guides for the lines.
+    pub guides: bool,
+}
+
+impl SnapSettings {
+    /// Moves the point onto the grid, or into line with the closest of `others` within `tolerance`.
+    ///
+    /// Returns the snapped point along with the guides showing what it was lined up with.
+    fn snap(
+        &self,
+        point: Vec2,
+        others: impl IntoIterator<Item = Vec2>,
+        tolerance: f32,
+    ) -> (Vec2, Vec<[Vec2; 2]>) {
+        let mut snapped = match self.grid {
+            Some(spacing) => (point / spacing.size()).round() * spacing.size(),
+            None => point,
+        };
+
+        let mut guides = Vec::new();
+        if self.guides {
+            let others = others.into_iter().collect::<Vec<_>>();
+            let closest_within_reach = |distance: fn(Vec2, Vec2) -> f32| {
+                others
+                    .iter()
+                    .copied()
+                    .filter(|other| distance(point, *other) <= tolerance)
+                    .min_by(|a, b| distance(point, *a).total_cmp(&distance(point, *b)))
+            };
+            let aligned_x = closest_within_reach(|a, b| (a.x - b.x).abs());
+            let aligned_y = closest_within_reach(|a, b| (a.y - b.y).abs());
+            if let Some(other) = aligned_x {
+                snapped.x = other.x;
+            }
+            if let Some(other) = aligned_y {
+                snapped.y = other.y;
+            }
+            // the guides end at the point only once it is snapped on both axes
+            guides.extend(aligned_x.map(|other| [other, Vec2::new(other.x, snapped.y)]));
+            guides.extend(aligned_y.map(|other| [other, Vec2::new(snapped.x, other.y)]));
+        }
+        (snapped, guides)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GridSpacing {
+    HalfMeter,
+    Meter,
+}
+impl GridSpacing {
+    pub const ALL: [GridSpacing; 2] = [GridSpacing::HalfMeter, GridSpacing::Meter];
+
+    /// The distance between grid lines, in pixels.
+    fn size(&self) -> f32 {
+        match self {
+            GridSpacing::HalfMeter => 0.5 * SIZE_SCALING_FACTOR,
+            GridSpacing::Meter => SIZE_SCALING_FACTOR,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GridSpacing::HalfMeter => "0.5 m",
+            GridSpacing::Meter => "1 m",
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetSnapSettings(pub SnapSettings);
+
+/// Tells the UI how dragged units and arrow ends are lined up.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapSettingsChanged(pub SnapSettings);
+
+/// How far the pointer has been dragged, in pixels, before snapping.
+///
+/// Snapping moves the dragged point away from the pointer, so the drag deltas can't be added up on
+/// the point itself.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct DragOffset(pub Vec2);
+
+/// The lines showing which units a dragged point was lined up with.
+#[derive(Resource, Default)]
+pub struct AlignmentGuides(Vec<[Vec2; 2]>);
+
+/// Lines up dragged points according to the [`SnapSettings`].
+#[derive(SystemParam)]
+pub struct Snapping<'w> {
+    r_snap_settings: Res<'w, SnapSettings>,
+    r_alignment_guides: ResMut<'w, AlignmentGuides>,
+}
+impl Snapping<'_> {
+    /// Moves the point onto the grid, or into line with the closest of `others` within reach.
+    pub fn snap(
+        &mut self,
+        point: Vec2,
+        others: impl IntoIterator<Item = Vec2>,
+        zoom_factor: f32,
+    ) -> Vec2 {
+        let (snapped, guides) =
+            self.r_snap_settings
+                .snap(point, others, GUIDE_TOLERANCE * zoom_factor);
+        self.show_guides(guides);
+        snapped
+    }
+
+    /// Hides the guides once the drag is over.
+    pub fn end(&mut self) {
+        self.show_guides(Vec::new());
+    }
+
+    fn show_guides(&mut self, guides: Vec<[Vec2; 2]>) {
+        // avoid triggering change detection, which would redraw the guides needlessly
+        if self.r_alignment_guides.0 != guides {
+            self.r_alignment_guides.0 = guides;
+        }
+    }
+}
+
+fn sys_set_snap_settings(
+    mut er_set_snap_settings: EventReader<SetSnapSettings>,
+    mut r_snap_settings: ResMut<SnapSettings>,
+) {
+    if let Some(SetSnapSettings(snap_settings)) = er_set_snap_settings.read().last() {
+        r_snap_settings.set_if_neq(*snap_settings);
+    }
+}
+
+fn sys_send_snap_settings_changed(
+    r_snap_settings: Res<SnapSettings>,
+    mut ew_snap_settings_changed: EventWriter<SnapSettingsChanged>,
+) {
+    ew_snap_settings_changed.send(SnapSettingsChanged(*r_snap_settings));
+}
+
+#[derive(Component)]
+struct GridOverlay;
+
+fn sys_update_grid_overlay(
+    r_snap_settings: Res<SnapSettings>,
+    q_grid_overlays: Query<Entity, With<GridOverlay>>,
+    mut commands: Commands,
+) {
+    q_grid_overlays.iter().for_each(|overlay| {
+        commands.entity(overlay).despawn();
+    });
+    let Some(spacing) = r_snap_settings.grid else {
+        return;
+    };
+
+    let half_extents = FIELD_HALF_EXTENTS * SIZE_SCALING_FACTOR;
+    let steps = (half_extents / spacing.size()).round().as_ivec2();
+    let mut grid_builder = PathBuilder::new();
+    for step in -steps.x..=steps.x {
+        let x = step as f32 * spacing.size();
+        grid_builder.move_to(Vec2::new(x, -half_extents.y));
+        grid_builder.line_to(Vec2::new(x, half_extents.y));
+    }
+    for step in -steps.y..=steps.y {
+        let y = step as f32 * spacing.size();
+        grid_builder.move_to(Vec2::new(-half_extents.x, y));
+        grid_builder.line_to(Vec2::new(half_extents.x, y));
+    }
+
+    commands.spawn((
+        ShapeBundle {
+            path: grid_builder.build(),
+            transform: Transform::from_xyz(0., 0., Z_LEVEL_GRID),
+            ..default()
+        },
+        Stroke::new(WHITE.with_alpha(0.3), 2.),
+        PickingBehavior::IGNORE,
+        GridOverlay,
+    ));
+}
+
+#[derive(Component)]
+struct AlignmentGuideLines;
+
+fn sys_update_alignment_guides(
+    r_alignment_guides: Res<AlignmentGuides>,
+    q_guide_lines: Query<Entity, With<AlignmentGuideLines>>,
+    mut commands: Commands,
+) {
+    q_guide_lines.iter().for_each(|guide_lines| {
+        commands.entity(guide_lines).despawn();
+    });
+    if r_alignment_guides.0.is_empty() {
+        return;
+    }
+
+    let mut guide_builder = PathBuilder::new();
+    for [from, to] in &r_alignment_guides.0 {
+        guide_builder.move_to(*from);
+        guide_builder.line_to(*to);
+    }
+    commands.spawn((
+        ShapeBundle {
+            path: guide_builder.build(),
+            transform: Transform::from_xyz(0., 0., Z_LEVEL_ALIGNMENT_GUIDES),
+            ..default()
+        },
+        Stroke::new(MAGENTA, 3.),
+        PickingBehavior::IGNORE,
+        AlignmentGuideLines,
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(grid: Option<GridSpacing>, guides: bool) -> SnapSettings {
+        SnapSettings { grid, guides }
+    }
+
+    #[test]
+    fn snap_leaves_point_without_settings() {
+        let point = Vec2::new(123.4, -56.7);
+        let others = [Vec2::new(124., -56.)];
+        assert_eq!(
+            settings(None, false).snap(point, others, GUIDE_TOLERANCE),
+            (point, vec![])
+        );
+    }
+
+    #[test]
+    fn snap_rounds_to_the_closest_grid_intersection() {
+        let point = Vec2::new(149., -51.);
+        let meter = settings(Some(GridSpacing::Meter), false);
+        assert_eq!(
+            meter.snap(point, [], GUIDE_TOLERANCE).0,
+            Vec2::new(100., -100.)
+        );
+        let half_meter = settings(Some(GridSpacing::HalfMeter), false);
+        assert_eq!(
+            half_meter.snap(point, [], GUIDE_TOLERANCE).0,
+            Vec2::new(150., -50.)
+        );
+    }
+
+    #[test]
+    fn snap_lines_up_with_others_within_tolerance() {
+        let point = Vec2::new(100., 200.);
+        let in_line_x = Vec2::new(105., 500.);
+        let in_line_y = Vec2::new(300., 193.);
+        let (snapped, guides) = settings(None, true).snap(point, [in_line_x, in_line_y], 8.);
+        assert_eq!(snapped, Vec2::new(105., 193.));
+        assert_eq!(guides, vec![[in_line_x, snapped], [in_line_y, snapped]]);
+    }
+
+    #[test]
+    fn snap_ignores_others_beyond_tolerance() {
+        let point = Vec2::new(100., 200.);
+        let (snapped, guides) = settings(None, true).snap(point, [Vec2::new(109., 300.)], 8.);
+        assert_eq!(snapped, point);
+        assert!(guides.is_empty());
+    }
+
+    #[test]
+    fn snap_picks_the_closest_other_per_axis() {
+        let point = Vec2::new(100., 200.);
+        let closest = Vec2::new(98.5, 400.);
+        let others = [Vec2::new(103., 300.), closest];
+        let (snapped, guides) = settings(None, true).snap(point, others, 8.);
+        assert_eq!(snapped, Vec2::new(98.5, 200.));
+        assert_eq!(guides, vec![[closest, Vec2::new(98.5, 200.)]]);
+    }
+
+    #[test]
+    fn snap_lines_up_after_snapping_to_the_grid() {
+        let point = Vec2::new(149., 200.);
+        let other = Vec2::new(147., 500.);
+        let (snapped, _) = settings(Some(GridSpacing::Meter), true).snap(point, [other], 8.);
+        assert_eq!(snapped, Vec2::new(147., 200.));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::bevy::{playback::Playback, timeline::Timeline};
+use bevy::prelude::*;
+
+pub struct StoneClockPlugin;
+impl Plugin for StoneClockPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<StoneClock>()
+            .add_event::<SetStoneInterval>()
+            .add_event::<StoneClockChanged>()
+            .add_systems(
+                Update,
+                sys_set_stone_interval.run_if(on_event::<SetStoneInterval>),
+            )
+            .add_systems(
+                Update,
+                sys_send_stone_clock_changed.run_if(
+                    resource_changed::<StoneClock>
+                        .or(resource_changed::<Playback>)
+                        .or(resource_changed::<Timeline>),
+                ),
+            );
+    }
+}
+
+const DEFAULT_INTERVAL: f32 = 1.5;
+const MIN_INTERVAL: f32 = 0.1;
+
+/// Counts the stones, i.e. the drum beats by which time is measured in jugger.
+///
+/// The clock runs through the whole play, so stones carry over from one step to the next.
+#[derive(Resource)]
+pub struct StoneClock {
+    interval: f32, // in seconds, between two stones
+}
+impl Default for StoneClock {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+}
+impl StoneClock {
+    /// The number of stones sounded until the given time of the play.
+    pub fn stone_at(&self, game_time: f32) -> u32 {
+        (game_time / self.interval).floor() as u32
+    }
+
+    /// The times of the stones after `from` up to and including `to`, in seconds since the start
+    /// of the play.
+    pub fn stones_between(&self, from: f32, to: f32) -> impl Iterator<Item = f32> + '_ {
+        (self.stone_at(from) + 1..=self.stone_at(to)).map(|stone| stone as f32 * self.interval)
+    }
+}
+
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct SetStoneInterval(pub f32);
+
+/// Tells the UI where in the play the playback is.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct StoneClockChanged {
+    pub stone: u32,
+    pub game_time: f32, // in seconds, since the start of the play
+    pub interval: f32,
+}
+
+fn sys_set_stone_interval(
+    mut er_set_stone_interval: EventReader<SetStoneInterval>,
+    mut r_stone_clock: ResMut<StoneClock>,
+) {
+    if let Some(SetStoneInterval(interval)) = er_set_stone_interval.read().last() {
+        r_stone_clock.interval = interval.max(MIN_INTERVAL);
+    }
+}
+
+fn sys_send_stone_clock_changed(
+    r_stone_clock: Res<StoneClock>,
+    r_playback: Res<Playback>,
+    r_timeline: Res<Timeline>,
+    mut ew_stone_clock_changed: EventWriter<StoneClockChanged>,
+) {
+    let game_time = r_timeline.current_start() + r_playback.time();
+    ew_stone_clock_changed.send(StoneClockChanged {
+        stone: r_stone_clock.stone_at(game_time),
+        game_time,
+        interval: r_stone_clock.interval,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stone_at_counts_the_stones_sounded() {
+        let clock = StoneClock { interval: 2. };
+        assert_eq!(clock.stone_at(0.), 0);
+        assert_eq!(clock.stone_at(1.9), 0);
+        assert_eq!(clock.stone_at(2.), 1);
+        assert_eq!(clock.stone_at(7.), 3);
+    }
+
+    #[test]
+    fn stones_between_excludes_from_and_includes_to() {
+        let clock = StoneClock { interval: 2. };
+        let stones = |from, to| clock.stones_between(from, to).collect::<Vec<_>>();
+        assert_eq!(stones(1., 5.), vec![2., 4.]);
+        assert_eq!(stones(2., 4.), vec![4.]);
+        assert_eq!(stones(4.5, 5.5), Vec::<f32>::new());
+    }
+}
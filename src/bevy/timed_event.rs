@@ -0,0 +1,240 @@
+use crate::bevy::{
+    playback::{playback_active, Playback, PlaybackOrigin},
+    scenario::RosterIndex,
+    selection::Selected,
+    stone_clock::StoneClock,
+    timeline::Timeline,
+    unit::{Unit, UnitState, UnitType, UNIT_RADIUS},
+    Z_LEVEL_EVENT_FLASHES,
+};
+use bevy::{color::palettes::css::*, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct TimedEventPlugin;
+impl Plugin for TimedEventPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_event::<TimedEventAction>()
+            .add_event::<TimedEventsChanged>()
+            .add_systems(
+                Update,
+                sys_apply_timed_event_action.run_if(on_event::<TimedEventAction>),
+            )
+            .add_systems(
+                Update,
+                sys_play_timed_events
+                    .after(sys_apply_timed_event_action)
+                    .run_if(playback_active),
+            )
+            .add_systems(Update, sys_fade_event_flashes)
+            .add_systems(
+                Update,
+                sys_send_timed_events_changed.run_if(resource_changed::<Timeline>),
+            );
+    }
+}
+
+/// Something happening to a unit at a given time of a step, e.g. it being hit.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub time: f32,   // in seconds, since the start of the step
+    pub unit: usize, // index into the units of the step, see [`RosterIndex`]
+    pub kind: TimedEventKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimedEventKind {
+    Hit { downtime: u8 },
+    Pin { downtime: u8 },
+    PickUpJugg,
+    DropJugg,
+}
+impl TimedEventKind {
+    pub fn label(&self) -> String {
+        match self {
+            TimedEventKind::Hit { downtime } => format!("Hit ({downtime} stones)"),
+            TimedEventKind::Pin { downtime } => format!("Pin ({downtime} stones)"),
+            TimedEventKind::PickUpJugg => "Picks up jugg".to_owned(),
+            TimedEventKind::DropJugg => "Drops jugg".to_owned(),
+        }
+    }
+
+    /// Changes the unit the way the event does, events not affecting the unit are ignored.
+    pub fn apply(&self, unit: &mut Unit) {
+        let Unit::Player {
+            unit_type, state, ..
+        } = unit
+        else {
+            return;
+        };
+        match (self, unit_type) {
+            (TimedEventKind::Hit { downtime }, _) => {
+                *state = UnitState::Inactive {
+                    downtime: *downtime,
+                    pin_stone: false,
+                };
+            }
+            (TimedEventKind::Pin { downtime }, _) => {
+                *state = UnitState::Pinned {
+                    downtime: *downtime,
+                };
+            }
+            (TimedEventKind::PickUpJugg, UnitType::Runner { has_jugg }) => *has_jugg = true,
+            (TimedEventKind::DropJugg, UnitType::Runner { has_jugg }) => *has_jugg = false,
+            (TimedEventKind::PickUpJugg | TimedEventKind::DropJugg, _) => {}
+        }
+    }
+
+    fn flash_color(&self) -> Srgba {
+        match self {
+            TimedEventKind::Hit { .. } => ORANGE_RED,
+            TimedEventKind::Pin { .. } => GOLD,
+            TimedEventKind::PickUpJugg | TimedEventKind::DropJugg => WHITE,
+        }
+    }
+}
+
+/// Edits the events of the current step.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum TimedEventAction {
+    /// Adds an event for the selected unit at the current playback time.
+    Add(TimedEventKind),
+    /// Removes the event at the given index of [`TimedEventsChanged`].
+    Remove(usize),
+}
+
+/// Tells the UI about the events of the current step.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct TimedEventsChanged(pub Vec<TimedEvent>);
+
+fn sys_apply_timed_event_action(
+    mut er_timed_event_action: EventReader<TimedEventAction>,
+    mut r_timeline: ResMut<Timeline>,
+    r_playback: Res<Playback>,
+    q_selected: Query<&RosterIndex, (With<Unit>, With<Selected>)>,
+) {
+    for action in er_timed_event_action.read() {
+        let events = r_timeline.current_events_mut();
+        match *action {
+            TimedEventAction::Add(kind) => {
+                let Ok(RosterIndex(unit)) = q_selected.get_single() else {
+                    warn!("Timed events can only be added for a selected unit.");
+                    continue;
+                };
+                let time = r_playback.time();
+                // after the events at the same time, so they are applied in the order added
+                let index = events.partition_point(|event| event.time <= time);
+                events.insert(
+                    index,
+                    TimedEvent {
+                        time,
+                        unit: *unit,
+                        kind,
+                    },
+                );
+            }
+            TimedEventAction::Remove(index) => {
+                if index < events.len() {
+                    events.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// How long the marker of an event stays visible, in seconds.
+const FLASH_DURATION: f32 = 0.6;
+
+/// A marker briefly shown where an event happened.
+#[derive(Component, Default)]
+struct EventFlash {
+    age: f32, // in seconds
+}
+
+/// Puts every played back unit into the state it has at the current time, i.e. its actual state
+/// with all events and stones up to then applied. Events happening since the last frame are
+/// marked on the board.
+fn sys_play_timed_events(
+    mut r_playback: ResMut<Playback>,
+    r_timeline: Res<Timeline>,
+    r_stone_clock: Res<StoneClock>,
+    mut q_units: Query<(&mut Unit, &Transform, &PlaybackOrigin, &RosterIndex)>,
+    mut commands: Commands,
+) {
+    let time = r_playback.time();
+    let step_start = r_timeline.current_start();
+    let events = &r_timeline.current_step().events;
+    for (mut unit, transform, origin, RosterIndex(index)) in q_units.iter_mut() {
+        let unit_events = events.iter().filter(|event| event.unit == *index);
+
+        // the events and stones are applied in the order they happen, both are sorted by time
+        let mut state = origin.unit();
+        let mut stones = r_stone_clock
+            .stones_between(step_start, step_start + time)
+            .map(|stone| stone - step_start)
+            .peekable();
+        for event in unit_events.clone().filter(|event| event.time <= time) {
+            while stones.next_if(|stone| *stone <= event.time).is_some() {
+                pass_stone(&mut state);
+            }
+            event.kind.apply(&mut state);
+        }
+        stones.for_each(|_| pass_stone(&mut state));
+        unit.set_if_neq(state);
+
+        // scrubbing backwards does not replay events
+        unit_events
+            .filter(|event| r_playback.just_happened(event.time))
+            .for_each(|event| {
+                spawn_event_flash(transform.translation.xy(), event.kind, &mut commands);
+            });
+    }
+    r_playback.mark_played();
+}
+
+fn pass_stone(unit: &mut Unit) {
+    if let Unit::Player { state, .. } = unit {
+        *state = state.after_stone();
+    }
+}
+
+fn spawn_event_flash(position: Vec2, kind: TimedEventKind, commands: &mut Commands) {
+    commands.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shapes::Circle {
+                radius: UNIT_RADIUS,
+                center: Vec2::ZERO,
+            }),
+            transform: Transform::from_translation(position.extend(Z_LEVEL_EVENT_FLASHES)),
+            ..default()
+        },
+        Stroke::new(kind.flash_color(), 8.),
+        EventFlash::default(),
+        PickingBehavior::IGNORE,
+    ));
+}
+
+/// Grows and fades the markers until they are gone.
+fn sys_fade_event_flashes(
+    time: Res<Time>,
+    mut q_flashes: Query<(Entity, &mut EventFlash, &mut Transform, &mut Stroke)>,
+    mut commands: Commands,
+) {
+    for (entity, mut flash, mut transform, mut stroke) in q_flashes.iter_mut() {
+        flash.age += time.delta_secs();
+        if flash.age >= FLASH_DURATION {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = flash.age / FLASH_DURATION;
+        transform.scale = Vec3::splat(1. + progress);
+        stroke.color.set_alpha(1. - progress);
+    }
+}
+
+fn sys_send_timed_events_changed(
+    r_timeline: Res<Timeline>,
+    mut ew_timed_events_changed: EventWriter<TimedEventsChanged>,
+) {
+    ew_timed_events_changed.send(TimedEventsChanged(r_timeline.current_step().events.clone()));
+}
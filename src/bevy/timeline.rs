@@ -0,0 +1,207 @@
+use crate::bevy::{
+    history::EditHistory,
+    playback::{interrupt_playback_if, BoardChangeSet},
+    scenario::{RosterIndex, ScenarioStep, StepCapture, StepLoader},
+    timed_event::TimedEvent,
+};
+use bevy::prelude::*;
+
+pub struct TimelinePlugin;
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<Timeline>()
+            .add_event::<TimelineAction>()
+            .add_event::<TimelineChanged>()
+            .add_systems(
+                Update,
+                sys_apply_timeline_action
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<TimelineAction>),
+            )
+            .add_systems(Update, interrupt_playback_if(on_event::<TimelineAction>))
+            .add_systems(
+                Update,
+                sys_send_timeline_changed.run_if(resource_changed::<Timeline>),
+            );
+    }
+}
+
+/// The steps of the play, of which the current one is shown on the board.
+///
+/// While a step is shown, the board is its source of truth, the stored step is only updated when
+/// leaving it, see [`StepCapture`].
+#[derive(Resource, Clone)]
+pub struct Timeline {
+    steps: Vec<ScenarioStep>, // never empty
+    current: usize,
+}
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+impl Timeline {
+    pub fn new(steps: Vec<ScenarioStep>) -> Self {
+        let steps = match steps.is_empty() {
+            true => vec![ScenarioStep::default()],
+            false => steps,
+        };
+        Self { steps, current: 0 }
+    }
+
+    pub fn steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    pub fn current_step(&self) -> &ScenarioStep {
+        &self.steps[self.current]
+    }
+
+    /// When the current step starts, in seconds since the start of the play.
+    pub fn current_start(&self) -> f32 {
+        self.steps[..self.current]
+            .iter()
+            .map(|step| step.duration)
+            .sum()
+    }
+
+    pub fn set_current_duration(&mut self, duration: f32) {
+        let current = self.current;
+        self.steps[current].duration = duration;
+    }
+
+    /// The events of the current step, which have to be kept sorted by time.
+    pub fn current_events_mut(&mut self) -> &mut Vec<TimedEvent> {
+        let current = self.current;
+        &mut self.steps[current].events
+    }
+
+    /// Stores the board as the current step. Units deleted from the board are removed from the other
+    /// steps as well, returns their roster indices.
+    ///
+    /// Units added to the board are added to the other steps where they stand on the board, since
+    /// every step lists the same units.
+    pub fn capture_current(&mut self, step_capture: &StepCapture) -> Vec<usize> {
+        let current = self.current;
+        let removed = step_capture.capture_into(&mut self.steps[current]);
+        let (before, rest) = self.steps.split_at_mut(current);
+        let (current_step, after) = rest.split_first_mut().expect("the current step exists");
+        for step in before.iter_mut().chain(after) {
+            step.remove_units(&removed);
+            if let Some(added) = current_step.units.get(step.units.len()..) {
+                step.units.extend_from_slice(added);
+            }
+        }
+        removed
+    }
+
+    /// Returns whether a different step has to be shown on the board.
+    fn apply(&mut self, action: TimelineAction) -> bool {
+        match action {
+            TimelineAction::AddStep => {
+                // the next step starts where the units ended up, but without the old arrows and events
+                let step = ScenarioStep {
+                    units: self.current_step().units_after_movement(),
+                    ..default()
+                };
+                self.current += 1;
+                self.steps.insert(self.current, step);
+                true
+            }
+            TimelineAction::DuplicateStep => {
+                let step = self.current_step().clone();
+                self.current += 1;
+                self.steps.insert(self.current, step);
+                true
+            }
+            TimelineAction::RemoveStep => {
+                if self.steps.len() <= 1 {
+                    return false;
+                }
+                self.steps.remove(self.current);
+                self.current = self.current.min(self.steps.len() - 1);
+                true
+            }
+            TimelineAction::MoveStepEarlier => {
+                if self.current > 0 {
+                    self.steps.swap(self.current, self.current - 1);
+                    self.current -= 1;
+                }
+                false
+            }
+            TimelineAction::MoveStepLater => {
+                if self.current + 1 < self.steps.len() {
+                    self.steps.swap(self.current, self.current + 1);
+                    self.current += 1;
+                }
+                false
+            }
+            TimelineAction::GoToStep(index) => {
+                if index >= self.steps.len() || index == self.current {
+                    return false;
+                }
+                self.current = index;
+                true
+            }
+        }
+    }
+}
+
+/// Edits the timeline, all actions except [`TimelineAction::GoToStep`] affect the current step.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelineAction {
+    AddStep,
+    DuplicateStep,
+    RemoveStep,
+    MoveStepEarlier,
+    MoveStepLater,
+    GoToStep(usize),
+}
+
+/// Tells the UI about the shape of the timeline.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimelineChanged {
+    pub step_count: usize,
+    pub current: usize,
+}
+
+fn sys_apply_timeline_action(
+    mut er_timeline_action: EventReader<TimelineAction>,
+    mut r_timeline: ResMut<Timeline>,
+    step_capture: StepCapture,
+    mut step_loader: StepLoader,
+    mut r_history: ResMut<EditHistory>,
+    q_roster: Query<(Entity, &RosterIndex)>,
+    mut commands: Commands,
+) {
+    let removed = r_timeline.capture_current(&step_capture);
+
+    let mut step_changed = false;
+    for action in er_timeline_action.read() {
+        step_changed |= r_timeline.apply(*action);
+    }
+    if step_changed {
+        step_loader.show(r_timeline.current_step());
+        // the edits refer to the entities of the previous board
+        r_history.clear();
+    } else if !removed.is_empty() {
+        // the board stays, so its units have to move up like those of the stored step
+        for (entity, roster_index) in q_roster.iter() {
+            if let Some(new_index) = roster_index.after_removal(&removed) {
+                commands.entity(entity).insert(new_index);
+            }
+        }
+        // the edits refer to the roster indices from before
+        r_history.clear();
+    }
+}
+
+fn sys_send_timeline_changed(
+    r_timeline: Res<Timeline>,
+    mut ew_timeline_changed: EventWriter<TimelineChanged>,
+) {
+    ew_timeline_changed.send(TimelineChanged {
+        step_count: r_timeline.steps.len(),
+        current: r_timeline.current,
+    });
+}
@@ -0,0 +1,262 @@
+use crate::bevy::{
+    arrow_kind::SelectArrowKind,
+    formation::{ApplyFormation, FormationsChanged},
+    history::{HistoryAction, HistoryChanged},
+    input::{InputModeChanged, SetInputMode},
+    lineup::{LineupAction, LineupsChanged},
+    playback::{PlaybackAction, PlaybackChanged},
+    roster::{EditSelectedUnit, RosterChanged, SpawnUnit},
+    scenario::{LoadScenario, RequestScenarioExport, ScenarioExported},
+    selection::{DeleteSelected, SelectionChanged},
+    snapping::{SetSnapSettings, SnapSettingsChanged},
+    stone_clock::{SetStoneInterval, StoneClockChanged},
+    timed_event::{TimedEventAction, TimedEventsChanged},
+    timeline::{TimelineAction, TimelineChanged},
+    unit_state::SetSelectedUnitState,
+};
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// Connects the UI with the board through a single channel per direction.
+///
+/// Commands from the UI are passed on as the events the plugins handle, and the events the plugins
+/// send to tell the UI about changes are collected into [`UiEvents`].
+pub struct UiBusPlugin;
+impl Plugin for UiBusPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_event::<UiCommand>()
+            .add_event::<UiEvents>()
+            .add_systems(
+                PreUpdate,
+                sys_dispatch_ui_commands.run_if(on_event::<UiCommand>),
+            )
+            .add_systems(PostUpdate, sys_collect_ui_events);
+    }
+}
+
+/// Everything the UI can ask the board to do.
+#[derive(Event, Clone, Debug)]
+pub enum UiCommand {
+    LoadScenario(LoadScenario),
+    RequestScenarioExport(RequestScenarioExport),
+    History(HistoryAction),
+    DeleteSelected(DeleteSelected),
+    SetSelectedUnitState(SetSelectedUnitState),
+    SpawnUnit(SpawnUnit),
+    EditSelectedUnit(EditSelectedUnit),
+    SelectArrowKind(SelectArrowKind),
+    SetInputMode(SetInputMode),
+    Timeline(TimelineAction),
+    Playback(PlaybackAction),
+    TimedEvent(TimedEventAction),
+    SetStoneInterval(SetStoneInterval),
+    Lineup(LineupAction),
+    ApplyFormation(ApplyFormation),
+    SetSnapSettings(SetSnapSettings),
+}
+
+/// Everything the board tells the UI about.
+#[derive(Clone, Debug)]
+pub enum UiEvent {
+    ScenarioExported(ScenarioExported),
+    HistoryChanged(HistoryChanged),
+    SelectionChanged(SelectionChanged),
+    RosterChanged(RosterChanged),
+    InputModeChanged(InputModeChanged),
+    TimelineChanged(TimelineChanged),
+    PlaybackChanged(PlaybackChanged),
+    TimedEventsChanged(TimedEventsChanged),
+    StoneClockChanged(StoneClockChanged),
+    LineupsChanged(LineupsChanged),
+    FormationsChanged(FormationsChanged),
+    SnapSettingsChanged(SnapSettingsChanged),
+}
+
+/// The events of a frame, sent together since the UI only sees the latest message of a channel.
+#[derive(Event, Clone, Debug)]
+pub struct UiEvents(pub Vec<UiEvent>);
+
+#[derive(SystemParam)]
+struct UiCommandWriters<'w> {
+    load_scenario: EventWriter<'w, LoadScenario>,
+    request_scenario_export: EventWriter<'w, RequestScenarioExport>,
+    history_action: EventWriter<'w, HistoryAction>,
+    delete_selected: EventWriter<'w, DeleteSelected>,
+    set_selected_unit_state: EventWriter<'w, SetSelectedUnitState>,
+    spawn_unit: EventWriter<'w, SpawnUnit>,
+    edit_selected_unit: EventWriter<'w, EditSelectedUnit>,
+    select_arrow_kind: EventWriter<'w, SelectArrowKind>,
+    set_input_mode: EventWriter<'w, SetInputMode>,
+    timeline_action: EventWriter<'w, TimelineAction>,
+    playback_action: EventWriter<'w, PlaybackAction>,
+    timed_event_action: EventWriter<'w, TimedEventAction>,
+    set_stone_interval: EventWriter<'w, SetStoneInterval>,
+    lineup_action: EventWriter<'w, LineupAction>,
+    apply_formation: EventWriter<'w, ApplyFormation>,
+    set_snap_settings: EventWriter<'w, SetSnapSettings>,
+}
+
+fn sys_dispatch_ui_commands(
+    mut er_ui_command: EventReader<UiCommand>,
+    mut writers: UiCommandWriters,
+) {
+    for command in er_ui_command.read().cloned() {
+        match command {
+            UiCommand::LoadScenario(event) => {
+                writers.load_scenario.send(event);
+            }
+            UiCommand::RequestScenarioExport(event) => {
+                writers.request_scenario_export.send(event);
+            }
+            UiCommand::History(event) => {
+                writers.history_action.send(event);
+            }
+            UiCommand::DeleteSelected(event) => {
+                writers.delete_selected.send(event);
+            }
+            UiCommand::SetSelectedUnitState(event) => {
+                writers.set_selected_unit_state.send(event);
+            }
+            UiCommand::SpawnUnit(event) => {
+                writers.spawn_unit.send(event);
+            }
+            UiCommand::EditSelectedUnit(event) => {
+                writers.edit_selected_unit.send(event);
+            }
+            UiCommand::SelectArrowKind(event) => {
+                writers.select_arrow_kind.send(event);
+            }
+            UiCommand::SetInputMode(event) => {
+                writers.set_input_mode.send(event);
+            }
+            UiCommand::Timeline(event) => {
+                writers.timeline_action.send(event);
+            }
+            UiCommand::Playback(event) => {
+                writers.playback_action.send(event);
+            }
+            UiCommand::TimedEvent(event) => {
+                writers.timed_event_action.send(event);
+            }
+            UiCommand::SetStoneInterval(event) => {
+                writers.set_stone_interval.send(event);
+            }
+            UiCommand::Lineup(event) => {
+                writers.lineup_action.send(event);
+            }
+            UiCommand::ApplyFormation(event) => {
+                writers.apply_formation.send(event);
+            }
+            UiCommand::SetSnapSettings(event) => {
+                writers.set_snap_settings.send(event);
+            }
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct UiEventReaders<'w, 's> {
+    scenario_exported: EventReader<'w, 's, ScenarioExported>,
+    history_changed: EventReader<'w, 's, HistoryChanged>,
+    selection_changed: EventReader<'w, 's, SelectionChanged>,
+    roster_changed: EventReader<'w, 's, RosterChanged>,
+    input_mode_changed: EventReader<'w, 's, InputModeChanged>,
+    timeline_changed: EventReader<'w, 's, TimelineChanged>,
+    playback_changed: EventReader<'w, 's, PlaybackChanged>,
+    timed_events_changed: EventReader<'w, 's, TimedEventsChanged>,
+    stone_clock_changed: EventReader<'w, 's, StoneClockChanged>,
+    lineups_changed: EventReader<'w, 's, LineupsChanged>,
+    formations_changed: EventReader<'w, 's, FormationsChanged>,
+    snap_settings_changed: EventReader<'w, 's, SnapSettingsChanged>,
+}
+
+fn sys_collect_ui_events(mut readers: UiEventReaders, mut ew_ui_events: EventWriter<UiEvents>) {
+    let mut events = Vec::new();
+    events.extend(
+        readers
+            .scenario_exported
+            .read()
+            .cloned()
+            .map(UiEvent::ScenarioExported),
+    );
+    events.extend(
+        readers
+            .history_changed
+            .read()
+            .copied()
+            .map(UiEvent::HistoryChanged),
+    );
+    events.extend(
+        readers
+            .selection_changed
+            .read()
+            .copied()
+            .map(UiEvent::SelectionChanged),
+    );
+    events.extend(
+        readers
+            .roster_changed
+            .read()
+            .copied()
+            .map(UiEvent::RosterChanged),
+    );
+    events.extend(
+        readers
+            .input_mode_changed
+            .read()
+            .copied()
+            .map(UiEvent::InputModeChanged),
+    );
+    events.extend(
+        readers
+            .timeline_changed
+            .read()
+            .copied()
+            .map(UiEvent::TimelineChanged),
+    );
+    events.extend(
+        readers
+            .playback_changed
+            .read()
+            .copied()
+            .map(UiEvent::PlaybackChanged),
+    );
+    events.extend(
+        readers
+            .timed_events_changed
+            .read()
+            .cloned()
+            .map(UiEvent::TimedEventsChanged),
+    );
+    events.extend(
+        readers
+            .stone_clock_changed
+            .read()
+            .copied()
+            .map(UiEvent::StoneClockChanged),
+    );
+    events.extend(
+        readers
+            .lineups_changed
+            .read()
+            .cloned()
+            .map(UiEvent::LineupsChanged),
+    );
+    events.extend(
+        readers
+            .formations_changed
+            .read()
+            .cloned()
+            .map(UiEvent::FormationsChanged),
+    );
+    events.extend(
+        readers
+            .snap_settings_changed
+            .read()
+            .copied()
+            .map(UiEvent::SnapSettingsChanged),
+    );
+
+    if !events.is_empty() {
+        ew_ui_events.send(UiEvents(events));
+    }
+}
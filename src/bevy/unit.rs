@@ -1,146 +1,47 @@
 use crate::bevy::{
-    arrow::{spawn_arrow, Arrow},
+    arrow::{spawn_arrow, spawn_arrow_preview, Arrow, ArrowAnchors, ArrowPreview},
+    arrow_kind::CurrentArrowKind,
     camera::ZoomState,
     from_meters,
-    input::InputMode,
+    history::{Edit, EditHistory},
+    input::{GlobalAction, InputMode},
+    selection::{select_grabbed, Selectable, Selected, SelectionRegistry},
+    snapping::{DragOffset, Snapping},
     Z_LEVEL_UNITS, Z_LEVEL_UNIT_SPRITES,
 };
-use bevy::{color::palettes::css::*, prelude::*};
+use bevy::{
+    color::palettes::css::*, ecs::system::SystemParam, picking::pointer::PointerId, prelude::*,
+};
 use bevy_prototype_lyon::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct UnitPlugin;
 impl Plugin for UnitPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.init_resource::<UnitRegistry>()
-            .add_systems(Startup, sys_spawn_default_units)
-            .add_systems(
-                Update,
-                sys_sync_selection_state.run_if(resource_changed::<UnitRegistry>),
-            )
-            .add_systems(Update, sys_update_unit_visuals)
+        app.add_systems(Update, sys_update_unit_visuals)
+            .add_systems(Update, sys_update_unit_fills)
+            .add_systems(Update, sys_update_unit_sprites)
+            .add_systems(Update, sys_complete_long_presses)
             .add_systems(
                 Update,
-                sys_on_input_mode_change.run_if(state_changed::<InputMode>),
+                sys_on_input_mode_change.run_if(state_changed::<InputMode>.or(units_added)),
             );
     }
 }
 
-fn sys_spawn_default_units(mut commands: Commands, r_asset_server: Res<AssetServer>) {
-    // TODO move these default units to external startup config
-    spawn_unit(SpawnData::Jugg, &mut commands, &r_asset_server);
-
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Runner { has_jugg: false },
-            Team::Left,
-            StartPosition::Runner,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::Shield),
-            Team::Left,
-            StartPosition::One,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::QTip),
-            Team::Left,
-            StartPosition::Two,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::Chain),
-            Team::Left,
-            StartPosition::Three,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::QTip),
-            Team::Left,
-            StartPosition::Four,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
+/// Size of the unit's background circle, also used to attach arrows dropped onto a unit.
+pub const UNIT_RADIUS: f32 = 45.;
 
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Runner { has_jugg: false },
-            Team::Right,
-            StartPosition::Runner,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::DoubleShort),
-            Team::Right,
-            StartPosition::One,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::Staff),
-            Team::Right,
-            StartPosition::Two,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::Long),
-            Team::Right,
-            StartPosition::Three,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-    spawn_unit(
-        SpawnData::Player(
-            UnitType::Player(PlayerUnitType::Chain),
-            Team::Right,
-            StartPosition::Four,
-        ),
-        &mut commands,
-        &r_asset_server,
-    );
-}
-
-enum SpawnData {
-    Jugg,
-    Player(UnitType, Team, StartPosition),
-}
-fn spawn_unit(spawn_data: SpawnData, commands: &mut Commands, r_asset_server: &Res<AssetServer>) {
-    let (position, unit_component) = match spawn_data {
-        SpawnData::Jugg => (Jugg::initial_position(), Unit::Jugg),
-        SpawnData::Player(unit_type, team, start_position) => (
-            team.initial_position(start_position),
-            Unit::Player {
-                team,
-                unit_type,
-                state: UnitState::Active,
-            },
-        ),
-    };
+pub fn spawn_unit_at(
+    unit_component: Unit,
+    position: Vec2,
+    commands: &mut Commands,
+    r_asset_server: &Res<AssetServer>,
+) -> Entity {
     let background_bundle = ShapeBundle {
         path: GeometryBuilder::build_as(&shapes::Circle {
-            radius: 45.,
+            radius: UNIT_RADIUS,
             center: Vec2::ZERO,
         }),
         transform: Transform::from_translation(position.extend(Z_LEVEL_UNITS)),
@@ -153,15 +54,18 @@ fn spawn_unit(spawn_data: SpawnData, commands: &mut Commands, r_asset_server: &R
             background_bundle,
             Fill::color(unit_component.color(false)),
             unit_component,
+            UnitDetails::default(),
+            Selectable,
         ))
         .with_child((
             sprite,
             Transform::from_xyz(0., 0., Z_LEVEL_UNIT_SPRITES),
             PickingBehavior::IGNORE,
-        ));
+        ))
+        .id()
 }
 
-#[derive(Component, Clone, Copy, Debug)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Unit {
     Jugg,
     Player {
@@ -173,7 +77,7 @@ pub enum Unit {
 impl Unit {
     fn color(&self, selected: bool) -> Color {
         // TODO make this customizable
-        Color::from(match (self, selected) {
+        let color = match (self, selected) {
             (Unit::Jugg, true) => WHITE_SMOKE,
             (Unit::Jugg, false) => LIGHT_GRAY,
             (Unit::Player { team, .. }, selected) => match (team, selected) {
@@ -182,7 +86,11 @@ impl Unit {
                 (Team::Right, true) => LIGHT_BLUE,
                 (Team::Right, false) => BLUE,
             },
-        })
+        };
+        match self {
+            Unit::Player { state, .. } if state.is_down() => Color::from(color.mix(&GRAY, 0.7)),
+            _ => Color::from(color),
+        }
     }
 
     fn get_sprite(&self, r_asset_server: &Res<AssetServer>) -> Sprite {
@@ -207,13 +115,22 @@ impl Unit {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Team {
     Left,
     Right,
 }
 impl Team {
-    fn initial_position(&self, start_position: StartPosition) -> Vec2 {
+    pub const ALL: [Team; 2] = [Team::Left, Team::Right];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Team::Left => "Left",
+            Team::Right => "Right",
+        }
+    }
+
+    pub fn initial_position(&self, start_position: StartPosition) -> Vec2 {
         match (self, start_position) {
             (Team::Left, StartPosition::One) => from_meters(-21., 4.),
             (Team::Left, StartPosition::Two) => from_meters(-21., 2.),
@@ -228,21 +145,65 @@ impl Team {
         }
     }
 }
-enum StartPosition {
+/// The slots a team lines up in at the start of a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartPosition {
     One,
     Two,
     Three,
     Four,
     Runner,
 }
+impl StartPosition {
+    pub const ALL: [StartPosition; 5] = [
+        StartPosition::One,
+        StartPosition::Two,
+        StartPosition::Three,
+        StartPosition::Four,
+        StartPosition::Runner,
+    ];
+}
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UnitType {
     Positional(PositionalUnitType),
     Runner { has_jugg: bool },
     Player(PlayerUnitType),
 }
-#[derive(Clone, Copy, Debug)]
+impl UnitType {
+    pub const ALL: [UnitType; 12] = [
+        UnitType::Positional(PositionalUnitType::One),
+        UnitType::Positional(PositionalUnitType::Two),
+        UnitType::Positional(PositionalUnitType::Three),
+        UnitType::Positional(PositionalUnitType::Four),
+        UnitType::Positional(PositionalUnitType::Five),
+        UnitType::Runner { has_jugg: false },
+        UnitType::Player(PlayerUnitType::Chain),
+        UnitType::Player(PlayerUnitType::Long),
+        UnitType::Player(PlayerUnitType::Staff),
+        UnitType::Player(PlayerUnitType::QTip),
+        UnitType::Player(PlayerUnitType::Shield),
+        UnitType::Player(PlayerUnitType::DoubleShort),
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnitType::Positional(PositionalUnitType::One) => "1",
+            UnitType::Positional(PositionalUnitType::Two) => "2",
+            UnitType::Positional(PositionalUnitType::Three) => "3",
+            UnitType::Positional(PositionalUnitType::Four) => "4",
+            UnitType::Positional(PositionalUnitType::Five) => "5",
+            UnitType::Runner { .. } => "Runner",
+            UnitType::Player(PlayerUnitType::Chain) => "Chain",
+            UnitType::Player(PlayerUnitType::Long) => "Long",
+            UnitType::Player(PlayerUnitType::Staff) => "Staff",
+            UnitType::Player(PlayerUnitType::QTip) => "Q-tip",
+            UnitType::Player(PlayerUnitType::Shield) => "Shield",
+            UnitType::Player(PlayerUnitType::DoubleShort) => "Double short",
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PositionalUnitType {
     One,
     Two,
@@ -250,7 +211,7 @@ pub enum PositionalUnitType {
     Four,
     Five,
 }
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PlayerUnitType {
     Chain,
     Long,
@@ -260,45 +221,85 @@ pub enum PlayerUnitType {
     DoubleShort,
 }
 
+/// What the coach noted about a unit, e.g. who plays it.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnitDetails {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<u8>, // on the jersey
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+}
+impl UnitDetails {
+    pub fn is_empty(&self) -> bool {
+        *self == UnitDetails::default()
+    }
+}
+
 #[derive(Component, Clone, Copy)]
 pub struct Jugg;
 impl Jugg {
-    fn initial_position() -> Vec2 {
+    pub fn initial_position() -> Vec2 {
         Vec2::ZERO
     }
 }
 
-#[derive(Component, Clone, Copy, Debug)]
-enum UnitState {
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitState {
     Active,
     Inactive { downtime: u8, pin_stone: bool },
     Pinned { downtime: u8 },
 }
+impl UnitState {
+    /// Whether the unit is out of play, i.e. inactive or pinned.
+    pub fn is_down(&self) -> bool {
+        !matches!(self, UnitState::Active)
+    }
 
-#[derive(Resource, Default)]
-pub struct UnitRegistry {
-    selected: Option<Entity>,
-}
-
-#[derive(Component, Clone, Debug)]
-pub struct Selected;
-
-fn sys_sync_selection_state(
-    r_unit_registry: Res<UnitRegistry>,
-    q_unit: Query<Entity, With<Unit>>,
-    mut commands: Commands,
-) {
-    q_unit.iter().for_each(|entity| {
-        commands.entity(entity).remove::<Selected>();
-    });
+    /// The stones left until the unit is active again.
+    pub fn downtime(&self) -> Option<u8> {
+        match self {
+            UnitState::Active => None,
+            UnitState::Inactive { downtime, .. } | UnitState::Pinned { downtime } => {
+                Some(*downtime)
+            }
+        }
+    }
 
-    if let Some(selected_entity) = r_unit_registry.selected {
-        if let Ok(entity) = q_unit.get(selected_entity) {
-            commands.entity(entity).insert(Selected);
-        } else {
-            error!("{selected_entity} is selected, but is not a unit entity.")
+    /// The state one stone later, the unit is active again once its downtime is over.
+    pub fn after_stone(self) -> UnitState {
+        match self {
+            UnitState::Active => UnitState::Active,
+            UnitState::Inactive { downtime, .. } | UnitState::Pinned { downtime }
+                if downtime <= 1 =>
+            {
+                UnitState::Active
+            }
+            UnitState::Inactive {
+                downtime,
+                pin_stone,
+            } => UnitState::Inactive {
+                downtime: downtime - 1,
+                pin_stone,
+            },
+            UnitState::Pinned { downtime } => UnitState::Pinned {
+                downtime: downtime - 1,
+            },
         }
     }
+
+    /// Whether a pin marker belongs next to the unit.
+    pub fn is_pinned(&self) -> bool {
+        matches!(
+            self,
+            UnitState::Pinned { .. }
+                | UnitState::Inactive {
+                    pin_stone: true,
+                    ..
+                }
+        )
+    }
 }
 
 fn sys_update_unit_visuals(
@@ -328,71 +329,352 @@ fn sys_update_unit_visuals(
     });
 }
 
+/// Keeps the fill in line with the unit, e.g. greying it out while it is down.
+fn sys_update_unit_fills(mut q_units: Query<(&mut Fill, &Unit, Has<Selected>), Changed<Unit>>) {
+    for (mut fill, unit, selected) in q_units.iter_mut() {
+        fill.color = unit.color(selected);
+    }
+}
+
+/// Keeps the sprite in line with the unit, e.g. when a runner picks up the jugg.
+fn sys_update_unit_sprites(
+    q_units: Query<(&Unit, &Children), Changed<Unit>>,
+    mut q_sprites: Query<&mut Sprite>,
+    r_asset_server: Res<AssetServer>,
+) {
+    for (unit, children) in q_units.iter() {
+        let mut sprites = q_sprites.iter_many_mut(children);
+        while let Some(mut sprite) = sprites.fetch_next() {
+            *sprite = unit.get_sprite(&r_asset_server);
+        }
+    }
+}
+
+fn units_added(q_added: Query<(), Added<Unit>>) -> bool {
+    !q_added.is_empty()
+}
+
+/// Marks the observers handling pointer input on units, so they can be replaced when the set of
+/// units changes.
+#[derive(Component)]
+struct UnitInputObserver;
+
 fn sys_on_input_mode_change(
     current_input_mode: Res<State<InputMode>>,
     q_units: Query<Entity, With<Unit>>,
+    q_observers: Query<Entity, With<UnitInputObserver>>,
     mut commands: Commands,
 ) {
     // TODO move this to input to have all variations in one place
 
-    println!("Input mode changed: {:?}", current_input_mode);
+    q_observers.iter().for_each(|observer| {
+        commands.entity(observer).despawn();
+    });
 
     let input_observers = match **current_input_mode {
         InputMode::View => None,
         InputMode::Position => Some(vec![
             Observer::new(on_unit_grabbed_do_select),
+            Observer::new(on_unit_drag_started_do_remember_position),
             Observer::new(on_unit_dragged_do_move),
+            Observer::new(on_unit_drag_ended_do_record_move),
         ]),
         InputMode::Movement => Some(vec![
             Observer::new(on_unit_grabbed_do_select),
-            Observer::new(on_unit_dragged_do_draw_arrow),
+            Observer::new(on_unit_drag_started_do_start_sketch),
+            Observer::new(on_unit_dragged_do_extend_sketch),
+            Observer::new(on_unit_drag_ended_do_draw_arrow),
         ]),
     };
     if let Some(observers) = input_observers {
         observers.into_iter().for_each(|mut observer| {
             q_units.iter().for_each(|unit| observer.watch_entity(unit));
-            commands.spawn((observer, StateScoped(**current_input_mode)));
+            commands.spawn((
+                observer,
+                UnitInputObserver,
+                StateScoped(**current_input_mode),
+            ));
         });
     }
 }
 
 fn on_unit_grabbed_do_select(
     trigger: Trigger<Pointer<Down>>,
-    mut r_unit_registry: ResMut<UnitRegistry>,
+    action_state: Res<ActionState<GlobalAction>>,
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+    r_time: Res<Time>,
+    mut commands: Commands,
 ) {
-    r_unit_registry.selected = Some(trigger.target);
+    // touch screens have no modifier keys, holding a unit toggles it instead
+    if let PointerId::Touch(touch_id) = trigger.pointer_id {
+        commands.entity(trigger.target).insert(LongPress {
+            touch_id,
+            since: r_time.elapsed_secs(),
+            previous_selection: r_selection_registry.selected().collect(),
+        });
+    }
+    select_grabbed(trigger.target, &action_state, &mut r_selection_registry);
+}
+
+/// How long a unit has to be held on a touch screen to toggle it in the selection, in seconds.
+const LONG_PRESS_DURATION: f32 = 0.5;
+/// How far the touch may wander while holding a unit, in logical pixels.
+const LONG_PRESS_TOLERANCE: f32 = 10.;
+
+/// A touch holding a unit, along with the selection before the unit was grabbed.
+#[derive(Component)]
+struct LongPress {
+    touch_id: u64,
+    since: f32,
+    previous_selection: Vec<Entity>,
 }
 
+fn sys_complete_long_presses(
+    q_long_presses: Query<(Entity, &LongPress)>,
+    r_touches: Res<Touches>,
+    r_time: Res<Time>,
+    mut r_selection_registry: ResMut<SelectionRegistry>,
+    mut commands: Commands,
+) {
+    for (entity, long_press) in q_long_presses.iter() {
+        let held = r_touches
+            .get_pressed(long_press.touch_id)
+            .is_some_and(|touch| touch.distance().length() < LONG_PRESS_TOLERANCE);
+        if held && r_time.elapsed_secs() - long_press.since < LONG_PRESS_DURATION {
+            continue;
+        }
+        if held {
+            r_selection_registry.replace(long_press.previous_selection.iter().copied());
+            r_selection_registry.toggle(entity);
+        }
+        commands.entity(entity).remove::<LongPress>();
+    }
+}
+
+/// The position a unit had when it started being dragged, so the whole drag can be undone at once.
+#[derive(Component)]
+struct DragStartPosition(Vec2);
+
+/// Dragging a selected unit moves all selected units along with it.
+fn on_unit_drag_started_do_remember_position(
+    trigger: Trigger<Pointer<DragStart>>,
+    q_position: Query<&Transform, With<Unit>>,
+    r_selection_registry: Res<SelectionRegistry>,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        // other buttons are reserved for moving the view
+        return;
+    }
+    let dragged = match r_selection_registry.is_selected(trigger.target) {
+        true => r_selection_registry.selected().collect(),
+        false => vec![trigger.target],
+    };
+    for entity in dragged {
+        if let Ok(transform) = q_position.get(entity) {
+            commands
+                .entity(entity)
+                .insert(DragStartPosition(transform.translation.xy()));
+        }
+    }
+    commands
+        .entity(trigger.target)
+        .insert(DragOffset::default());
+}
+
+/// Snaps the grabbed unit into place, the other dragged units keep their distance to it.
 fn on_unit_dragged_do_move(
     trigger: Trigger<Pointer<Drag>>,
-    mut q_position: Query<&mut Transform, With<Unit>>,
+    mut q_dragged: Query<(&mut Transform, &DragStartPosition), With<Unit>>,
+    q_others: Query<&Transform, (With<Unit>, Without<DragStartPosition>)>,
+    mut q_offset: Query<&mut DragOffset>,
     r_zoom_state: Res<ZoomState>,
+    mut snapping: Snapping,
 ) {
-    if let Ok(mut target_transform) = q_position.get_mut(trigger.target) {
-        let mut delta = trigger.delta;
-        delta.y *= -1.;
-        delta *= r_zoom_state.current_zoom_factor;
-        target_transform.translation += delta.extend(0.);
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let (Ok(mut offset), Ok((_, DragStartPosition(grabbed_start)))) = (
+        q_offset.get_mut(trigger.target),
+        q_dragged.get(trigger.target),
+    ) else {
+        return;
+    };
+    let mut delta = trigger.delta;
+    delta.y *= -1.;
+    delta *= r_zoom_state.current_zoom_factor;
+    offset.0 += delta;
+
+    let grabbed_start = *grabbed_start;
+    let snapped = snapping.snap(
+        grabbed_start + offset.0,
+        q_others.iter().map(|transform| transform.translation.xy()),
+        r_zoom_state.current_zoom_factor,
+    );
+    for (mut transform, DragStartPosition(start)) in q_dragged.iter_mut() {
+        let position = *start + snapped - grabbed_start;
+        transform.translation = position.extend(transform.translation.z);
     }
 }
 
-fn on_unit_dragged_do_draw_arrow(
+fn on_unit_drag_ended_do_record_move(
     trigger: Trigger<Pointer<DragEnd>>,
+    q_position: Query<(Entity, &Transform, &DragStartPosition), With<Unit>>,
+    mut r_history: ResMut<EditHistory>,
+    mut snapping: Snapping,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    snapping.end();
+    commands.entity(trigger.target).remove::<DragOffset>();
+    let mut moves = Vec::new();
+    for (unit, transform, DragStartPosition(from)) in q_position.iter() {
+        let to = transform.translation.xy();
+        if *from != to {
+            moves.push(Edit::MoveUnit {
+                unit,
+                from: *from,
+                to,
+            });
+        }
+        commands.entity(unit).remove::<DragStartPosition>();
+    }
+    if let Some(edit) = Edit::group(moves) {
+        r_history.record(edit);
+    }
+}
+
+/// The path traced while drawing an arrow from a unit, along with the preview showing it.
+#[derive(Component)]
+struct ArrowSketch {
+    points: Vec<Vec2>,
+    preview: Option<Entity>,
+}
+
+fn on_unit_drag_started_do_start_sketch(
+    trigger: Trigger<Pointer<DragStart>>,
     q_position: Query<&Transform, With<Unit>>,
-    r_zoom_state: Res<ZoomState>,
     mut commands: Commands,
 ) {
-    if let Ok(unit) = q_position.get(trigger.target) {
-        let unit_position = unit.translation.xy();
-        let mut drag_distance = trigger.distance;
-        drag_distance.y *= -1.;
-        drag_distance *= r_zoom_state.current_zoom_factor;
-        spawn_arrow(
-            Arrow::Straight {
-                from: unit_position,
-                to: unit_position + drag_distance,
-            },
-            &mut commands,
-        );
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    if let Ok(transform) = q_position.get(trigger.target) {
+        commands.entity(trigger.target).insert(ArrowSketch {
+            points: vec![transform.translation.xy()],
+            preview: None,
+        });
     }
 }
+
+/// Snaps the end of the arrow sketched from a unit, while the sketch itself follows the pointer.
+#[derive(SystemParam)]
+struct SketchSnapping<'w, 's> {
+    q_position: Query<'w, 's, (Entity, &'static Transform), With<Unit>>,
+    r_zoom_state: Res<'w, ZoomState>,
+    snapping: Snapping<'w>,
+}
+impl SketchSnapping<'_, '_> {
+    fn snap_end(&mut self, arrow: Arrow, sketching_unit: Entity) -> Arrow {
+        let others = self
+            .q_position
+            .iter()
+            .filter(|(entity, _)| *entity != sketching_unit)
+            .map(|(_, transform)| transform.translation.xy());
+        let end = self
+            .snapping
+            .snap(arrow.end(), others, self.r_zoom_state.current_zoom_factor);
+        arrow.with_end(end)
+    }
+}
+
+fn on_unit_dragged_do_extend_sketch(
+    trigger: Trigger<Pointer<Drag>>,
+    mut q_sketches: Query<&mut ArrowSketch>,
+    mut q_previews: Query<&mut ArrowPreview>,
+    r_current_arrow_kind: Res<CurrentArrowKind>,
+    mut sketch_snapping: SketchSnapping,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let Ok(mut sketch) = q_sketches.get_mut(trigger.target) else {
+        return;
+    };
+    let Some(&last_point) = sketch.points.last() else {
+        return;
+    };
+    let mut delta = trigger.delta;
+    delta.y *= -1.;
+    delta *= sketch_snapping.r_zoom_state.current_zoom_factor;
+    sketch.points.push(last_point + delta);
+
+    let Some(arrow) = Arrow::from_sketch(&sketch.points) else {
+        return;
+    };
+    let arrow = sketch_snapping.snap_end(arrow, trigger.target);
+    match sketch
+        .preview
+        .and_then(|preview| q_previews.get_mut(preview).ok())
+    {
+        Some(mut preview) => preview.0 = arrow,
+        None => {
+            sketch.preview = Some(spawn_arrow_preview(
+                arrow,
+                r_current_arrow_kind.0,
+                &mut commands,
+            ))
+        }
+    }
+}
+
+fn on_unit_drag_ended_do_draw_arrow(
+    trigger: Trigger<Pointer<DragEnd>>,
+    q_sketches: Query<&ArrowSketch>,
+    r_current_arrow_kind: Res<CurrentArrowKind>,
+    mut r_history: ResMut<EditHistory>,
+    mut sketch_snapping: SketchSnapping,
+    mut commands: Commands,
+) {
+    if trigger.button != PointerButton::Primary {
+        return;
+    }
+    let Ok(ArrowSketch { points, preview }) = q_sketches.get(trigger.target) else {
+        return;
+    };
+    if let Some(preview) = preview {
+        commands.entity(*preview).despawn();
+    }
+    commands.entity(trigger.target).remove::<ArrowSketch>();
+    let snapped =
+        Arrow::from_sketch(points).map(|arrow| sketch_snapping.snap_end(arrow, trigger.target));
+    sketch_snapping.snapping.end();
+    let Some(mut arrow) = snapped else {
+        return;
+    };
+
+    // arrows dropped onto another unit stick to it
+    let target_unit = sketch_snapping
+        .q_position
+        .iter()
+        .filter(|(entity, _)| *entity != trigger.target)
+        .find(|(_, transform)| transform.translation.xy().distance(arrow.end()) <= UNIT_RADIUS);
+    if let Some((_, target_transform)) = target_unit {
+        arrow = arrow.with_end(target_transform.translation.xy());
+    }
+    let anchors = ArrowAnchors {
+        from: Some(trigger.target),
+        to: target_unit.map(|(entity, _)| entity),
+    };
+
+    let kind = r_current_arrow_kind.0;
+    r_history.record(Edit::CreateArrow {
+        arrow_entity: spawn_arrow(arrow.clone(), kind, anchors, &mut commands),
+        arrow,
+        kind,
+        anchors,
+    });
+}
@@ -0,0 +1,162 @@
+use crate::bevy::{
+    history::{Edit, EditHistory},
+    playback::{interrupt_playback_if, BoardChangeSet},
+    selection::Selected,
+    unit::{Unit, UnitDetails, UnitState, UNIT_RADIUS},
+    Z_LEVEL_UNIT_OVERLAYS,
+};
+use bevy::{color::palettes::css::*, prelude::*};
+use bevy_prototype_lyon::prelude::*;
+
+pub struct UnitStatePlugin;
+impl Plugin for UnitStatePlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_event::<SetSelectedUnitState>()
+            .add_systems(
+                Update,
+                sys_set_selected_unit_state
+                    .in_set(BoardChangeSet)
+                    .run_if(on_event::<SetSelectedUnitState>),
+            )
+            .add_systems(
+                Update,
+                interrupt_playback_if(on_event::<SetSelectedUnitState>),
+            )
+            .add_systems(Update, sys_update_unit_state_overlays);
+    }
+}
+
+/// Puts the selected players in or out of play.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetSelectedUnitState(pub UnitState);
+
+fn sys_set_selected_unit_state(
+    mut er_set_selected_unit_state: EventReader<SetSelectedUnitState>,
+    mut q_selected: Query<(Entity, &mut Unit, &UnitDetails), With<Selected>>,
+    mut r_history: ResMut<EditHistory>,
+) {
+    let Some(SetSelectedUnitState(new_state)) = er_set_selected_unit_state.read().last() else {
+        return;
+    };
+    let mut changes = Vec::new();
+    for (entity, mut unit, details) in q_selected.iter_mut() {
+        let before = *unit;
+        if let Unit::Player { state, .. } = unit.as_mut() {
+            *state = *new_state;
+        }
+        if *unit != before {
+            changes.push(Edit::ChangeUnit {
+                unit: entity,
+                before,
+                after: *unit,
+                details_before: details.clone(),
+                details_after: details.clone(),
+            });
+        }
+    }
+    if let Some(edit) = Edit::group(changes) {
+        r_history.record(edit);
+    }
+}
+
+const BADGE_RADIUS: f32 = 18.;
+const PIN_RADIUS: f32 = 10.;
+const KNEELING_OVERLAY_COLOR: Color = Color::srgba(0., 0., 0., 0.45);
+
+/// Marks the children of a unit showing its state, so they can be replaced when it changes.
+#[derive(Component)]
+struct UnitStateOverlay;
+
+/// Shows units which are out of play: kneeling, with the stones they still have to wait and a
+/// marker if they are pinned.
+fn sys_update_unit_state_overlays(
+    q_units: Query<(Entity, &Unit, Option<&Children>), Changed<Unit>>,
+    q_overlays: Query<(), With<UnitStateOverlay>>,
+    mut commands: Commands,
+) {
+    for (entity, unit, children) in q_units.iter() {
+        children
+            .into_iter()
+            .flat_map(|children| children.iter())
+            .filter(|child| q_overlays.contains(**child))
+            .for_each(|child| commands.entity(*child).despawn_recursive());
+
+        let Unit::Player { state, .. } = unit else {
+            continue;
+        };
+        if let Some(downtime) = state.downtime() {
+            commands.entity(entity).with_children(|parent| {
+                spawn_kneeling_overlay(parent);
+                spawn_downtime_badge(downtime, parent);
+                if state.is_pinned() {
+                    spawn_pin_marker(parent);
+                }
+            });
+        }
+    }
+}
+
+/// Darkens the whole unit, as a kneeling player is hidden behind the ones still standing.
+fn spawn_kneeling_overlay(parent: &mut ChildBuilder) {
+    parent.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::build_as(&shapes::Circle {
+                radius: UNIT_RADIUS,
+                center: Vec2::ZERO,
+            }),
+            transform: Transform::from_xyz(0., 0., Z_LEVEL_UNIT_OVERLAYS),
+            ..default()
+        },
+        Fill::color(KNEELING_OVERLAY_COLOR),
+        UnitStateOverlay,
+        PickingBehavior::IGNORE,
+    ));
+}
+
+/// Counts down the stones left, at the upper right of the unit.
+fn spawn_downtime_badge(downtime: u8, parent: &mut ChildBuilder) {
+    let offset = Vec2::splat(UNIT_RADIUS * 0.75);
+    parent
+        .spawn((
+            ShapeBundle {
+                path: GeometryBuilder::build_as(&shapes::Circle {
+                    radius: BADGE_RADIUS,
+                    center: Vec2::ZERO,
+                }),
+                transform: Transform::from_translation(offset.extend(Z_LEVEL_UNIT_OVERLAYS)),
+                ..default()
+            },
+            Fill::color(WHITE),
+            Stroke::new(BLACK, 2.),
+            UnitStateOverlay,
+            PickingBehavior::IGNORE,
+        ))
+        .with_child((
+            Text2d::new(downtime.to_string()),
+            TextFont::from_font_size(24.),
+            TextColor(BLACK.into()),
+            Transform::from_xyz(0., 0., 0.1),
+            PickingBehavior::IGNORE,
+        ));
+}
+
+/// A map pin at the upper left of the unit.
+fn spawn_pin_marker(parent: &mut ChildBuilder) {
+    let head = shapes::Circle {
+        radius: PIN_RADIUS,
+        center: Vec2::new(0., PIN_RADIUS),
+    };
+    let needle = shapes::Line(Vec2::ZERO, Vec2::new(0., -PIN_RADIUS));
+    let offset = Vec2::new(-UNIT_RADIUS * 0.75, UNIT_RADIUS * 0.75);
+    parent.spawn((
+        ShapeBundle {
+            path: GeometryBuilder::new().add(&head).add(&needle).build(),
+            transform: Transform::from_translation(offset.extend(Z_LEVEL_UNIT_OVERLAYS)),
+            ..default()
+        },
+        Fill::color(GOLD),
+        Stroke::new(BLACK, 3.),
+        UnitStateOverlay,
+        PickingBehavior::IGNORE,
+    ));
+}
@@ -0,0 +1,47 @@
+use crate::{
+    bevy::{
+        arrow_kind::{ArrowKind, SelectArrowKind},
+        input::{InputMode, InputModeChanged},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::prelude::*;
+
+/// Chooses the kind of arrow to draw, only shown while drawing arrows.
+#[component]
+pub fn ArrowKindToolbar() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let input_mode = move || {
+        ui_bus
+            .input_mode
+            .get()
+            .map(|InputModeChanged(input_mode)| input_mode)
+            .unwrap_or_default()
+    };
+    let (current_kind, set_current_kind) = signal(ArrowKind::default());
+
+    view! {
+        <Show when=move || input_mode() == InputMode::Movement>
+            <div class="buttons">
+                {ArrowKind::ALL
+                    .into_iter()
+                    .map(|kind| {
+                        let on_select = move |_| {
+                            set_current_kind.set(kind);
+                            ui_bus.send(UiCommand::SelectArrowKind(SelectArrowKind(kind)));
+                        };
+                        view! {
+                            <button
+                                class:selected=move || current_kind.get() == kind
+                                on:click=on_select
+                            >
+                                {kind.label()}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    bevy::{formation::ApplyFormation, ui_bus::UiCommand, unit::Team},
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Moves the players of a team into one of the formations.
+#[component]
+pub fn FormationControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let formations = move || {
+        ui_bus
+            .formations
+            .get()
+            .map(|formations| formations.0)
+            .unwrap_or_default()
+    };
+    let (selected_formation, set_selected_formation) = signal(String::new());
+
+    let formation_button = move |team: Team| {
+        let on_click = move |_| {
+            ui_bus.send(UiCommand::ApplyFormation(ApplyFormation {
+                team,
+                formation: selected_formation.get(),
+            }));
+        };
+        view! {
+            <button on:click=on_click disabled=move || selected_formation.read().is_empty()>
+                {format!("Form up {}", team.label())}
+            </button>
+        }
+    };
+
+    view! {
+        <div class="buttons">
+            <select
+                prop:value=selected_formation
+                on:change=move |evt: ev::Event| set_selected_formation.set(event_target_value(&evt))
+            >
+                <option value="" disabled>"Choose a formation"</option>
+                {move || {
+                    formations()
+                        .into_iter()
+                        .map(|name| {
+                            let value = name.clone();
+                            view! { <option value=value>{name}</option> }
+                        })
+                        .collect_view()
+                }}
+            </select>
+            {Team::ALL.into_iter().map(formation_button).collect_view()}
+        </div>
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{
+    bevy::{history::HistoryAction, ui_bus::UiCommand},
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::prelude::*;
+
+#[component]
+pub fn HistoryControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let history = move || ui_bus.history.get().unwrap_or_default();
+
+    let on_undo = move |_| ui_bus.send(UiCommand::History(HistoryAction::Undo));
+    let on_redo = move |_| ui_bus.send(UiCommand::History(HistoryAction::Redo));
+
+    view! {
+        <div class="buttons">
+            <button on:click=on_undo disabled=move || !history().can_undo title="Undo (Ctrl+Z)">
+                Undo
+            </button>
+            <button
+                on:click=on_redo
+                disabled=move || !history().can_redo
+                title="Redo (Ctrl+Shift+Z)"
+            >
+                Redo
+            </button>
+        </div>
+    }
+}
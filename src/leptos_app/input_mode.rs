@@ -0,0 +1,43 @@
+use crate::{
+    bevy::{
+        input::{InputMode, InputModeChanged, SetInputMode},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::prelude::*;
+
+/// Switches between the input modes, which are otherwise only reachable by keyboard.
+#[component]
+pub fn InputModeToolbar() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let input_mode = move || {
+        ui_bus
+            .input_mode
+            .get()
+            .map(|InputModeChanged(input_mode)| input_mode)
+            .unwrap_or_default()
+    };
+
+    view! {
+        <div class="buttons">
+            {InputMode::ALL
+                .into_iter()
+                .map(|mode| {
+                    let on_select = move |_| {
+                        ui_bus.send(UiCommand::SetInputMode(SetInputMode(mode)));
+                    };
+                    view! {
+                        <button
+                            class:selected=move || input_mode() == mode
+                            on:click=on_select
+                            title=format!("{} ({})", mode.label(), mode.shortcut())
+                        >
+                            {mode.label()}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
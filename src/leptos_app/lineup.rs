@@ -0,0 +1,63 @@
+use crate::{
+    bevy::{lineup::LineupAction, ui_bus::UiCommand},
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Picks a lineup to put on the board, and saves the board as a lineup of its own.
+#[component]
+pub fn LineupControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let lineups = Signal::derive(move || ui_bus.lineups.get().unwrap_or_default());
+    let (selected_lineup, set_selected_lineup) = signal(String::new());
+    let (new_lineup_name, set_new_lineup_name) = signal(String::new());
+    let is_user_lineup = move || lineups.read().user_lineups.contains(&selected_lineup.get());
+
+    let send = move |action: LineupAction| ui_bus.send(UiCommand::Lineup(action));
+    let on_apply = move |_| send(LineupAction::Apply(selected_lineup.get()));
+    let on_delete = move |_| send(LineupAction::Delete(selected_lineup.get()));
+    let on_save = move |_| {
+        let name = new_lineup_name.get();
+        if !name.trim().is_empty() {
+            send(LineupAction::Save(name.trim().to_owned()));
+        }
+    };
+
+    let options = move |names: Vec<String>| {
+        names
+            .into_iter()
+            .map(|name| {
+                let value = name.clone();
+                view! { <option value=value>{name}</option> }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="buttons">
+            <select
+                prop:value=selected_lineup
+                on:change=move |evt: ev::Event| set_selected_lineup.set(event_target_value(&evt))
+            >
+                <option value="" disabled>"Choose a lineup"</option>
+                <optgroup label="Shipped">{move || options(lineups.get().default_lineups)}</optgroup>
+                <optgroup label="Yours">{move || options(lineups.get().user_lineups)}</optgroup>
+            </select>
+            <button on:click=on_apply disabled=move || selected_lineup.read().is_empty()>
+                Line up
+            </button>
+            <button on:click=on_delete disabled=move || !is_user_lineup()>
+                Delete lineup
+            </button>
+        </div>
+        <div class="buttons">
+            <input
+                type="text"
+                placeholder="Lineup name"
+                prop:value=new_lineup_name
+                on:input=move |evt: ev::Event| set_new_lineup_name.set(event_target_value(&evt))
+            />
+            <button on:click=on_save>Save board as lineup</button>
+        </div>
+    }
+}
@@ -0,0 +1,111 @@
+mod arrow_kind;
+mod formation;
+mod history;
+mod input_mode;
+mod lineup;
+mod palette;
+mod playback;
+mod scenario_io;
+mod selection;
+mod snapping;
+mod stone_clock;
+mod timed_event;
+mod timeline;
+mod ui_bus;
+mod unit_editor;
+mod unit_state;
+
+use crate::{
+    bevy::{
+        init_bevy_for_leptos,
+        selection::Selected,
+        unit::{Unit, UnitDetails},
+        EventChannels, QueryDuplexes,
+    },
+    RENDER_HEIGHT, RENDER_WIDTH,
+};
+use arrow_kind::ArrowKindToolbar;
+use bevy::prelude::With;
+use formation::FormationControls;
+use history::HistoryControls;
+use input_mode::InputModeToolbar;
+use leptos::{ev, prelude::*};
+use leptos_bevy_canvas::prelude::*;
+use lineup::LineupControls;
+use palette::{on_drop_spawn, UnitPalette};
+use playback::PlaybackControls;
+use scenario_io::ScenarioControls;
+use selection::SelectionControls;
+use snapping::SnapControls;
+use stone_clock::StoneClockDisplay;
+use timed_event::TimedEventsPanel;
+use timeline::TimelineStrip;
+use ui_bus::UiBus;
+use unit_editor::UnitEditor;
+
+#[component]
+pub fn App() -> impl IntoView {
+    let (selected_unit, selected_unit_qd) =
+        single_query_signal::<(Unit, UnitDetails), With<Selected>>();
+    let (ui_bus, ui_command_receiver, ui_events_sender) = UiBus::new();
+    provide_context(ui_bus);
+    let dragged_unit = RwSignal::new(None);
+    let on_drop = on_drop_spawn(ui_bus, dragged_unit);
+
+    view! {
+        <Frame id="bevy-frame" max_dimensions=(RENDER_WIDTH + 20., RENDER_HEIGHT + 20.)>
+            <BevyCanvas
+                init=move || {
+                    init_bevy_for_leptos(
+                        QueryDuplexes { selected_unit_qd },
+                        EventChannels {
+                            ui_command_receiver,
+                            ui_events_sender,
+                        },
+                    )
+                }
+                {..}
+                width=RENDER_WIDTH
+                height=RENDER_HEIGHT
+                on:dragover=|evt: ev::DragEvent| evt.prevent_default()
+                on:drop=on_drop
+            />
+        </Frame>
+
+        <Frame id="leptos-frame">
+            <InputModeToolbar />
+            <TimelineStrip />
+            <PlaybackControls />
+            <StoneClockDisplay />
+            <TimedEventsPanel />
+            <ScenarioControls />
+            <HistoryControls />
+            <SelectionControls />
+            <UnitPalette dragged_unit />
+            <LineupControls />
+            <FormationControls />
+            <SnapControls />
+            <ArrowKindToolbar />
+            <UnitEditor selected=Signal::derive(move || selected_unit.get()) />
+        </Frame>
+    }
+}
+
+#[component]
+pub fn Frame(
+    id: &'static str,
+    #[prop(optional)] max_dimensions: Option<(f32, f32)>,
+    children: Children,
+) -> impl IntoView {
+    let div_style = match max_dimensions {
+        Some((max_width, max_height)) => {
+            format!("max-width: {}px; max-height: {}px", max_width, max_height)
+        }
+        None => "".to_owned(),
+    };
+    view! {
+        <div id=id class="frame" style=div_style>
+            {children()}
+        </div>
+    }
+}
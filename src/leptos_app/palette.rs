@@ -0,0 +1,98 @@
+use crate::{
+    bevy::{
+        roster::{SpawnUnit, MAX_PLAYERS_PER_TEAM},
+        ui_bus::UiCommand,
+        unit::{Team, Unit, UnitState, UnitType},
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Lists every unit which can be put on the board, either by dragging it onto the field or by
+/// clicking it, which places it at the center of the view.
+///
+/// The dragged unit is handed to the drop target via `dragged_unit`, see [`on_drop_spawn`].
+#[component]
+pub fn UnitPalette(dragged_unit: RwSignal<Option<Unit>>) -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let roster = move || ui_bus.roster.get().unwrap_or_default();
+
+    let unit_button = move |unit: Unit, label: &'static str| {
+        let on_click = move |_| {
+            ui_bus.send(UiCommand::SpawnUnit(SpawnUnit {
+                unit,
+                screen_position: None,
+            }));
+        };
+        let on_drag_start = move |evt: ev::DragEvent| {
+            // some browsers only start dragging once there is data to drag
+            if let Some(data_transfer) = evt.data_transfer() {
+                data_transfer.set_data("text/plain", label).ok();
+            }
+            dragged_unit.set(Some(unit));
+        };
+        view! {
+            <button
+                draggable="true"
+                disabled=move || !roster().has_room_for(&unit)
+                on:click=on_click
+                on:dragstart=on_drag_start
+            >
+                {label}
+            </button>
+        }
+    };
+
+    let team_row = move |team: Team| {
+        let buttons = UnitType::ALL
+            .into_iter()
+            .map(|unit_type| {
+                let unit = Unit::Player {
+                    team,
+                    unit_type,
+                    state: UnitState::Active,
+                };
+                unit_button(unit, unit_type.label())
+            })
+            .collect_view();
+        let count = move || {
+            let players = roster().players(team);
+            match players >= MAX_PLAYERS_PER_TEAM {
+                true => format!("{}: {players}/{MAX_PLAYERS_PER_TEAM}, full", team.label()),
+                false => format!("{}: {players}/{MAX_PLAYERS_PER_TEAM}", team.label()),
+            }
+        };
+        view! {
+            <div class="buttons">
+                <span>{count}</span>
+                {buttons}
+            </div>
+        }
+    };
+
+    view! {
+        {Team::ALL.into_iter().map(team_row).collect_view()}
+        <div class="buttons">{unit_button(Unit::Jugg, "Jugg")}</div>
+    }
+}
+
+/// Handles dropping a unit from the [`UnitPalette`] onto the canvas.
+pub fn on_drop_spawn(
+    ui_bus: UiBus,
+    dragged_unit: RwSignal<Option<Unit>>,
+) -> impl Fn(ev::DragEvent) + Clone + 'static {
+    move |evt: ev::DragEvent| {
+        evt.prevent_default();
+        let Some(unit) = dragged_unit.get_untracked() else {
+            return;
+        };
+        dragged_unit.set(None);
+        ui_bus.send(UiCommand::SpawnUnit(SpawnUnit {
+            unit,
+            screen_position: Some(bevy::math::Vec2::new(
+                evt.offset_x() as f32,
+                evt.offset_y() as f32,
+            )),
+        }));
+    }
+}
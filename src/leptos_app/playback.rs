@@ -0,0 +1,60 @@
+use crate::{
+    bevy::{playback::PlaybackAction, ui_bus::UiCommand},
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+#[component]
+pub fn PlaybackControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let playback = ui_bus.playback;
+    let playing = move || playback.get().is_some_and(|playback| playback.playing);
+    let time = move || playback.get().map_or(0., |playback| playback.time);
+    let duration = move || playback.get().map_or(0., |playback| playback.duration);
+    let speed = move || playback.get().map_or(0., |playback| playback.speed);
+
+    let send = move |action: PlaybackAction| ui_bus.send(UiCommand::Playback(action));
+    // sends the action for the number entered into the input triggering the event
+    let send_value = move |to_action: fn(f32) -> PlaybackAction| {
+        move |evt: ev::Event| {
+            if let Ok(value) = event_target_value(&evt).parse::<f32>() {
+                send(to_action(value));
+            }
+        }
+    };
+
+    let on_play_pause = move |_| match playing() {
+        true => send(PlaybackAction::Pause),
+        false => send(PlaybackAction::Play),
+    };
+    let on_stop = move |_| send(PlaybackAction::Stop);
+    let on_scrub = send_value(PlaybackAction::Scrub);
+    let on_speed = send_value(PlaybackAction::SetSpeed);
+    let on_duration = send_value(PlaybackAction::SetStepDuration);
+
+    view! {
+        <div class="buttons">
+            <button on:click=on_play_pause>
+                {move || if playing() { "Pause" } else { "Play" }}
+            </button>
+            <button on:click=on_stop>Stop</button>
+        </div>
+        <input type="range" min="0" max=duration step="0.1" prop:value=time on:input=on_scrub />
+        <div class="buttons">
+            <label>
+                "Speed (m/s) "
+                <input type="number" min="0.1" step="0.5" prop:value=speed on:change=on_speed />
+            </label>
+            <label>
+                "Step duration (s) "
+                <input
+                    type="number"
+                    min="0.1"
+                    step="0.5"
+                    prop:value=duration
+                    on:change=on_duration
+                />
+            </label>
+        </div>
+    }
+}
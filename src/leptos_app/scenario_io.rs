@@ -0,0 +1,139 @@
+use crate::{
+    bevy::{
+        scenario::{
+            ExportTarget, LoadScenario, RequestScenarioExport, Scenario, ScenarioExported,
+            SCENARIO_URL_PREFIX,
+        },
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*, task::spawn_local};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, HtmlInputElement, Url};
+
+pub const SCENARIO_FILE_EXTENSION: &str = ".jugger.json";
+
+#[component]
+pub fn ScenarioControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let (share_link, set_share_link) = signal(None::<String>);
+
+    Effect::new(move || {
+        let Some(ScenarioExported { scenario, target }) = ui_bus.scenario_exported.get() else {
+            return;
+        };
+        match target {
+            ExportTarget::File => {
+                if let Err(err) = download_scenario(&scenario) {
+                    leptos::logging::error!("failed to save scenario: {err:?}");
+                }
+            }
+            ExportTarget::Link => match share_scenario(&scenario) {
+                Ok(link) => set_share_link.set(Some(link)),
+                Err(err) => leptos::logging::error!("failed to share scenario: {err:?}"),
+            },
+        }
+    });
+
+    let request_export = move |target: ExportTarget| {
+        ui_bus.send(UiCommand::RequestScenarioExport(RequestScenarioExport(
+            target,
+        )));
+    };
+    let on_save = move |_| request_export(ExportTarget::File);
+    let on_share = move |_| request_export(ExportTarget::Link);
+
+    let on_open = move |evt: ev::Event| {
+        let input = event_target::<HtmlInputElement>(&evt);
+        let Some(file) = input.files().and_then(|files| files.get(0)) else {
+            return;
+        };
+        // allow opening the same file again later on
+        input.set_value("");
+
+        spawn_local(async move {
+            match read_scenario(file).await {
+                Ok(scenario) => ui_bus.send(UiCommand::LoadScenario(LoadScenario(scenario))),
+                Err(err) => leptos::logging::error!("failed to open scenario: {err}"),
+            }
+        });
+    };
+
+    view! {
+        <div class="buttons">
+            <button on:click=on_save>Save</button>
+            <label>
+                Open
+                <input
+                    type="file"
+                    accept=format!("{SCENARIO_FILE_EXTENSION},application/json")
+                    style="display: none"
+                    on:change=on_open
+                />
+            </label>
+            <button on:click=on_share>Share</button>
+        </div>
+        {move || {
+            share_link
+                .get()
+                .map(|link| {
+                    view! {
+                        <input
+                            type="text"
+                            readonly
+                            prop:value=link
+                            on:focus=|evt| {
+                                event_target::<HtmlInputElement>(&evt).select();
+                            }
+                        />
+                    }
+                })
+        }}
+    }
+}
+
+/// Puts the scenario into the URL fragment and returns the resulting link.
+fn share_scenario(scenario: &Scenario) -> Result<String, JsValue> {
+    let fragment = scenario
+        .to_url_fragment()
+        .map_err(|err| JsValue::from_str(&err))?;
+    let location = window().location();
+    location.set_hash(&format!("{SCENARIO_URL_PREFIX}{fragment}"))?;
+    location.href()
+}
+
+fn download_scenario(scenario: &Scenario) -> Result<(), JsValue> {
+    let json = serde_json::to_string_pretty(scenario)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let file_name = match scenario.metadata.name.trim() {
+        "" => "scenario",
+        name => name,
+    };
+
+    let options = BlobPropertyBag::new();
+    options.set_type("application/json");
+    let blob = Blob::new_with_str_sequence_and_options(
+        &js_sys::Array::of1(&JsValue::from_str(&json)),
+        &options,
+    )?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    let anchor: HtmlAnchorElement = document().create_element("a")?.unchecked_into();
+    anchor.set_href(&url);
+    anchor.set_download(&format!("{file_name}{SCENARIO_FILE_EXTENSION}"));
+    anchor.click();
+
+    Url::revoke_object_url(&url)
+}
+
+async fn read_scenario(file: web_sys::File) -> Result<Scenario, String> {
+    let text = JsFuture::from(file.text())
+        .await
+        .map_err(|err| format!("{err:?}"))?
+        .as_string()
+        .ok_or_else(|| "file content is not text".to_owned())?;
+
+    serde_json::from_str(&text).map_err(|err| err.to_string())
+}
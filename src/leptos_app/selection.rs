@@ -0,0 +1,25 @@
+use crate::{
+    bevy::{
+        selection::{DeleteSelected, SelectionChanged},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::prelude::*;
+
+#[component]
+pub fn SelectionControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let nothing_selected =
+        move || ui_bus.selection.get().unwrap_or_default() == SelectionChanged::Nothing;
+
+    let on_delete = move |_| ui_bus.send(UiCommand::DeleteSelected(DeleteSelected));
+
+    view! {
+        <div class="buttons">
+            <button on:click=on_delete disabled=nothing_selected title="Delete (Del)">
+                Delete
+            </button>
+        </div>
+    }
+}
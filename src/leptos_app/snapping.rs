@@ -0,0 +1,70 @@
+use crate::{
+    bevy::{
+        snapping::{GridSpacing, SetSnapSettings, SnapSettings},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Toggles the grid and the alignment guides which dragged units and arrow ends snap to.
+#[component]
+pub fn SnapControls() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let snap_settings = move || {
+        ui_bus
+            .snap_settings
+            .get()
+            .map(|changed| changed.0)
+            .unwrap_or_default()
+    };
+    let set_snap_settings = move |snap_settings: SnapSettings| {
+        ui_bus.send(UiCommand::SetSnapSettings(SetSnapSettings(snap_settings)));
+    };
+
+    let grid_value = move || {
+        snap_settings()
+            .grid
+            .map_or("off", |spacing| spacing.label())
+    };
+    let on_grid = move |evt: ev::Event| {
+        let value = event_target_value(&evt);
+        set_snap_settings(SnapSettings {
+            grid: GridSpacing::ALL
+                .into_iter()
+                .find(|spacing| spacing.label() == value),
+            ..snap_settings()
+        });
+    };
+    let on_guides = move |evt: ev::Event| {
+        set_snap_settings(SnapSettings {
+            guides: event_target_checked(&evt),
+            ..snap_settings()
+        });
+    };
+
+    view! {
+        <div class="buttons">
+            <label>
+                "Grid "
+                <select prop:value=grid_value on:change=on_grid>
+                    <option value="off">Off</option>
+                    {GridSpacing::ALL
+                        .into_iter()
+                        .map(|spacing| {
+                            view! { <option value=spacing.label()>{spacing.label()}</option> }
+                        })
+                        .collect_view()}
+                </select>
+            </label>
+            <label>
+                "Alignment guides "
+                <input
+                    type="checkbox"
+                    prop:checked=move || snap_settings().guides
+                    on:change=on_guides
+                />
+            </label>
+        </div>
+    }
+}
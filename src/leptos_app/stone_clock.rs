@@ -0,0 +1,45 @@
+use crate::{
+    bevy::{stone_clock::SetStoneInterval, ui_bus::UiCommand},
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Shows the stone count and game clock of the playback, and sets the time between stones.
+#[component]
+pub fn StoneClockDisplay() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let clock = ui_bus.stone_clock;
+    let stone = move || clock.get().map_or(0, |clock| clock.stone);
+    let game_time = move || clock.get().map_or(0., |clock| clock.game_time);
+    let interval = move || clock.get().map_or(0., |clock| clock.interval);
+
+    let on_interval = move |evt: ev::Event| {
+        if let Ok(interval) = event_target_value(&evt).parse::<f32>() {
+            ui_bus.send(UiCommand::SetStoneInterval(SetStoneInterval(interval)));
+        }
+    };
+
+    view! {
+        <div class="buttons">
+            <span>{move || format!("Stone {}", stone())}</span>
+            <span>{move || format_game_time(game_time())}</span>
+            <label>
+                "Stone interval (s) "
+                <input
+                    type="number"
+                    min="0.1"
+                    step="0.1"
+                    prop:value=interval
+                    on:change=on_interval
+                />
+            </label>
+        </div>
+    }
+}
+
+/// As minutes and seconds, e.g. `1:05.3`.
+fn format_game_time(game_time: f32) -> String {
+    let minutes = (game_time / 60.).floor();
+    let seconds = game_time - minutes * 60.;
+    format!("{minutes}:{seconds:04.1}")
+}
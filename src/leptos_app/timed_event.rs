@@ -0,0 +1,69 @@
+use crate::{
+    bevy::{
+        timed_event::{TimedEventAction, TimedEventKind, TimedEventsChanged},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::{ev, prelude::*};
+
+/// Lists the events of the current step and adds new ones for the selected unit at the current
+/// playback time.
+#[component]
+pub fn TimedEventsPanel() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let events = move || {
+        ui_bus
+            .timed_events
+            .get()
+            .map(|TimedEventsChanged(events)| events)
+            .unwrap_or_default()
+    };
+    let (downtime, set_downtime) = signal(1u8);
+    let send = move |action: TimedEventAction| ui_bus.send(UiCommand::TimedEvent(action));
+
+    let add_button = move |to_kind: fn(u8) -> TimedEventKind, label: &'static str| {
+        let on_click = move |_| send(TimedEventAction::Add(to_kind(downtime.get())));
+        view! { <button on:click=on_click>{label}</button> }
+    };
+    let on_downtime = move |evt: ev::Event| {
+        if let Ok(value) = event_target_value(&evt).parse::<u8>() {
+            set_downtime.set(value);
+        }
+    };
+
+    let event_list = move || {
+        events()
+            .into_iter()
+            .enumerate()
+            .map(|(index, event)| {
+                let on_remove = move |_| send(TimedEventAction::Remove(index));
+                view! {
+                    <li>
+                        {format!(
+                            "{:.1}s, unit {}: {} ",
+                            event.time,
+                            event.unit + 1,
+                            event.kind.label(),
+                        )}
+                        <button on:click=on_remove>Remove</button>
+                    </li>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="buttons">
+            {add_button(|downtime| TimedEventKind::Hit { downtime }, "Hit")}
+            {add_button(|downtime| TimedEventKind::Pin { downtime }, "Pin")}
+            {add_button(|_| TimedEventKind::PickUpJugg, "Pick up jugg")}
+            {add_button(|_| TimedEventKind::DropJugg, "Drop jugg")}
+            <label>
+                "Downtime (stones) "
+                <input type="number" min="0" max="255" prop:value=downtime on:change=on_downtime />
+            </label>
+        </div>
+        <ul>{event_list}</ul>
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    bevy::{
+        timeline::{TimelineAction, TimelineChanged},
+        ui_bus::UiCommand,
+    },
+    leptos_app::ui_bus::UiBus,
+};
+use leptos::prelude::*;
+
+/// Shows the steps of the play, to switch between them and to edit the current one.
+#[component]
+pub fn TimelineStrip() -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let timeline = move || {
+        ui_bus.timeline.get().unwrap_or(TimelineChanged {
+            step_count: 1,
+            current: 0,
+        })
+    };
+    let send = move |action: TimelineAction| ui_bus.send(UiCommand::Timeline(action));
+
+    let steps = move || {
+        let TimelineChanged {
+            step_count,
+            current,
+        } = timeline();
+        (0..step_count)
+            .map(|index| {
+                let on_select = move |_| send(TimelineAction::GoToStep(index));
+                view! {
+                    <button class:selected=index == current on:click=on_select>
+                        {format!("Step {}", index + 1)}
+                    </button>
+                }
+            })
+            .collect_view()
+    };
+
+    let action_button = move |action: TimelineAction, label: &'static str| {
+        let on_click = move |_| send(action);
+        view! { <button on:click=on_click>{label}</button> }
+    };
+
+    view! {
+        <div class="buttons">{steps}</div>
+        <div class="buttons">
+            {action_button(TimelineAction::AddStep, "Add step")}
+            {action_button(TimelineAction::DuplicateStep, "Duplicate")}
+            {action_button(TimelineAction::RemoveStep, "Remove")}
+            {action_button(TimelineAction::MoveStepEarlier, "Move earlier")}
+            {action_button(TimelineAction::MoveStepLater, "Move later")}
+        </div>
+    }
+}
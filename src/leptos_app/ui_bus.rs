@@ -0,0 +1,108 @@
+use crate::bevy::{
+    formation::FormationsChanged,
+    history::HistoryChanged,
+    input::InputModeChanged,
+    lineup::LineupsChanged,
+    playback::PlaybackChanged,
+    roster::RosterChanged,
+    scenario::ScenarioExported,
+    selection::SelectionChanged,
+    snapping::SnapSettingsChanged,
+    stone_clock::StoneClockChanged,
+    timed_event::TimedEventsChanged,
+    timeline::TimelineChanged,
+    ui_bus::{UiCommand, UiEvent, UiEvents},
+};
+use leptos::prelude::*;
+use leptos_bevy_canvas::prelude::*;
+
+/// The UI side of the channels to the board, provided as context to every panel.
+///
+/// Each kind of [`UiEvent`] ends up in a signal of its own, holding the latest one received.
+#[derive(Clone, Copy)]
+pub struct UiBus {
+    ui_command_sender: StoredValue<LeptosEventSender<UiCommand>>,
+    pub scenario_exported: ReadSignal<Option<ScenarioExported>>,
+    pub history: ReadSignal<Option<HistoryChanged>>,
+    pub selection: ReadSignal<Option<SelectionChanged>>,
+    pub roster: ReadSignal<Option<RosterChanged>>,
+    pub input_mode: ReadSignal<Option<InputModeChanged>>,
+    pub timeline: ReadSignal<Option<TimelineChanged>>,
+    pub playback: ReadSignal<Option<PlaybackChanged>>,
+    pub timed_events: ReadSignal<Option<TimedEventsChanged>>,
+    pub stone_clock: ReadSignal<Option<StoneClockChanged>>,
+    pub lineups: ReadSignal<Option<LineupsChanged>>,
+    pub formations: ReadSignal<Option<FormationsChanged>>,
+    pub snap_settings: ReadSignal<Option<SnapSettingsChanged>>,
+}
+
+impl UiBus {
+    /// Returns the bus along with the ends to hand to the board, see
+    /// [`crate::bevy::EventChannels`].
+    pub fn new() -> (
+        Self,
+        BevyEventReceiver<UiCommand>,
+        BevyEventSender<UiEvents>,
+    ) {
+        let (ui_command_sender, ui_command_receiver) = event_l2b::<UiCommand>();
+        let (ui_events_receiver, ui_events_sender) = event_b2l::<UiEvents>();
+
+        let (scenario_exported, set_scenario_exported) = signal(None);
+        let (history, set_history) = signal(None);
+        let (selection, set_selection) = signal(None);
+        let (roster, set_roster) = signal(None);
+        let (input_mode, set_input_mode) = signal(None);
+        let (timeline, set_timeline) = signal(None);
+        let (playback, set_playback) = signal(None);
+        let (timed_events, set_timed_events) = signal(None);
+        let (stone_clock, set_stone_clock) = signal(None);
+        let (lineups, set_lineups) = signal(None);
+        let (formations, set_formations) = signal(None);
+        let (snap_settings, set_snap_settings) = signal(None);
+
+        Effect::new(move || {
+            let Some(UiEvents(events)) = ui_events_receiver.get() else {
+                return;
+            };
+            for event in events {
+                match event {
+                    UiEvent::ScenarioExported(event) => set_scenario_exported.set(Some(event)),
+                    UiEvent::HistoryChanged(event) => set_history.set(Some(event)),
+                    UiEvent::SelectionChanged(event) => set_selection.set(Some(event)),
+                    UiEvent::RosterChanged(event) => set_roster.set(Some(event)),
+                    UiEvent::InputModeChanged(event) => set_input_mode.set(Some(event)),
+                    UiEvent::TimelineChanged(event) => set_timeline.set(Some(event)),
+                    UiEvent::PlaybackChanged(event) => set_playback.set(Some(event)),
+                    UiEvent::TimedEventsChanged(event) => set_timed_events.set(Some(event)),
+                    UiEvent::StoneClockChanged(event) => set_stone_clock.set(Some(event)),
+                    UiEvent::LineupsChanged(event) => set_lineups.set(Some(event)),
+                    UiEvent::FormationsChanged(event) => set_formations.set(Some(event)),
+                    UiEvent::SnapSettingsChanged(event) => set_snap_settings.set(Some(event)),
+                }
+            }
+        });
+
+        let ui_bus = Self {
+            ui_command_sender: StoredValue::new(ui_command_sender),
+            scenario_exported,
+            history,
+            selection,
+            roster,
+            input_mode,
+            timeline,
+            playback,
+            timed_events,
+            stone_clock,
+            lineups,
+            formations,
+            snap_settings,
+        };
+        (ui_bus, ui_command_receiver, ui_events_sender)
+    }
+
+    pub fn send(&self, command: UiCommand) {
+        self.ui_command_sender.with_value(|sender| {
+            sender.send(command).ok();
+        });
+    }
+}
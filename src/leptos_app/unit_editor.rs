@@ -0,0 +1,174 @@
+use crate::{
+    bevy::{
+        roster::{EditSelectedUnit, MAX_PLAYERS_PER_TEAM},
+        selection::SelectionChanged,
+        ui_bus::UiCommand,
+        unit::{Team, Unit, UnitDetails, UnitState, UnitType},
+        unit_state::SetSelectedUnitState,
+    },
+    leptos_app::{ui_bus::UiBus, unit_state::UnitStateControls},
+};
+use leptos::{ev, prelude::*};
+
+/// Edits the selected unit, changes are applied to the board right away and can be undone.
+#[component]
+pub fn UnitEditor(#[prop(into)] selected: Signal<Option<(Unit, UnitDetails)>>) -> impl IntoView {
+    let ui_bus = expect_context::<UiBus>();
+    let set_selected = move |(unit, details): (Unit, UnitDetails)| {
+        ui_bus.send(UiCommand::EditSelectedUnit(EditSelectedUnit {
+            unit,
+            details,
+        }));
+    };
+    let unit = move || selected.get().map(|(unit, _)| unit);
+    let details = move || {
+        selected
+            .get()
+            .map(|(_, details)| details)
+            .unwrap_or_default()
+    };
+    let edit_unit = move |edit: &dyn Fn(&mut Unit)| {
+        if let Some((mut unit, details)) = selected.get() {
+            edit(&mut unit);
+            set_selected((unit, details));
+        }
+    };
+    let edit_details = move |edit: &dyn Fn(&mut UnitDetails)| {
+        if let Some((unit, mut details)) = selected.get() {
+            edit(&mut details);
+            set_selected((unit, details));
+        }
+    };
+
+    let current_team = move || match unit() {
+        Some(Unit::Player { team, .. }) => Some(team),
+        _ => None,
+    };
+    let team = move || current_team().map_or("", |team| team.label());
+    // a player can't be moved to a team which is full already
+    let team_is_full = move |team: Team| {
+        let players = ui_bus.roster.get().unwrap_or_default().players(team);
+        current_team() != Some(team) && players >= MAX_PLAYERS_PER_TEAM
+    };
+    let unit_type = move || match unit() {
+        Some(Unit::Player { unit_type, .. }) => unit_type.label(),
+        _ => "",
+    };
+    let unit_state = Signal::derive(move || match unit() {
+        Some(Unit::Player { state, .. }) => Some(state),
+        Some(Unit::Jugg) | None => None,
+    });
+
+    let on_team = move |evt: ev::Event| {
+        let label = event_target_value(&evt);
+        let Some(new_team) = Team::ALL.into_iter().find(|team| team.label() == label) else {
+            return;
+        };
+        edit_unit(&|unit| {
+            if let Unit::Player { team, .. } = unit {
+                *team = new_team;
+            }
+        });
+    };
+    let on_unit_type = move |evt: ev::Event| {
+        let label = event_target_value(&evt);
+        let Some(new_unit_type) = UnitType::ALL
+            .into_iter()
+            .find(|unit_type| unit_type.label() == label)
+        else {
+            return;
+        };
+        edit_unit(&|unit| {
+            if let Unit::Player { unit_type, .. } = unit {
+                *unit_type = new_unit_type;
+            }
+        });
+    };
+    let set_unit_state = Callback::new(move |state: UnitState| {
+        ui_bus.send(UiCommand::SetSelectedUnitState(SetSelectedUnitState(state)));
+    });
+    let on_name = move |evt: ev::Event| {
+        let name = event_target_value(&evt);
+        edit_details(&|details| details.name = name.clone());
+    };
+    // an empty or invalid number clears it
+    let on_number = move |evt: ev::Event| {
+        let number = event_target_value(&evt).parse::<u8>().ok();
+        edit_details(&|details| details.number = number);
+    };
+    let on_notes = move |evt: ev::Event| {
+        let notes = event_target_value(&evt);
+        edit_details(&|details| details.notes = notes.clone());
+    };
+
+    view! {
+        <Show
+            when=move || selected.get().is_some()
+            fallback=move || match ui_bus.selection.get() {
+                Some(SelectionChanged::Group(count)) => {
+                    view! { <h3>{format!("Selected: {count} elements")}</h3> }.into_any()
+                }
+                _ => view! { <h3>"Selected: -nothing-"</h3> }.into_any(),
+            }
+        >
+            <Show
+                when=move || unit_state.get().is_some()
+                fallback=|| view! { <h3>"Selected: Jugg"</h3> }
+            >
+                <div class="buttons">
+                    <label>
+                        "Team "
+                        <select prop:value=team on:change=on_team>
+                            {Team::ALL
+                                .into_iter()
+                                .map(|team| {
+                                    view! {
+                                        <option value=team.label() disabled=move || team_is_full(team)>
+                                            {team.label()}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                    <label>
+                        "Type "
+                        <select prop:value=unit_type on:change=on_unit_type>
+                            {UnitType::ALL
+                                .into_iter()
+                                .map(|unit_type| {
+                                    view! {
+                                        <option value=unit_type.label()>{unit_type.label()}</option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </label>
+                </div>
+                <UnitStateControls unit_state set_unit_state />
+            </Show>
+            <div class="buttons">
+                <label>
+                    "Name "
+                    <input type="text" prop:value=move || details().name on:change=on_name />
+                </label>
+                <label>
+                    "Number "
+                    <input
+                        type="number"
+                        min="0"
+                        max="255"
+                        prop:value=move || {
+                            details().number.map_or(String::new(), |number| number.to_string())
+                        }
+                        on:change=on_number
+                    />
+                </label>
+            </div>
+            <label>
+                "Notes "
+                <textarea prop:value=move || details().notes on:change=on_notes />
+            </label>
+        </Show>
+    }
+}
@@ -0,0 +1,98 @@
+use crate::bevy::unit::UnitState;
+use leptos::{ev, prelude::*};
+
+/// Sets whether the selected player is in play, only shown while a player is selected.
+#[component]
+pub fn UnitStateControls(
+    #[prop(into)] unit_state: Signal<Option<UnitState>>,
+    #[prop(into)] set_unit_state: Callback<UnitState>,
+) -> impl IntoView {
+    let downtime = move || {
+        unit_state
+            .get()
+            .and_then(|state| state.downtime())
+            .unwrap_or(0)
+    };
+    let pin_stone = move || {
+        matches!(
+            unit_state.get(),
+            Some(UnitState::Inactive {
+                pin_stone: true,
+                ..
+            })
+        )
+    };
+    let state_name = move || match unit_state.get() {
+        Some(UnitState::Inactive { .. }) => "inactive",
+        Some(UnitState::Pinned { .. }) => "pinned",
+        Some(UnitState::Active) | None => "active",
+    };
+
+    // keeps the downtime when switching between being inactive and pinned
+    let on_state = move |evt: ev::Event| {
+        let state = match event_target_value(&evt).as_str() {
+            "inactive" => UnitState::Inactive {
+                downtime: downtime(),
+                pin_stone: false,
+            },
+            "pinned" => UnitState::Pinned {
+                downtime: downtime(),
+            },
+            _ => UnitState::Active,
+        };
+        set_unit_state.run(state);
+    };
+    let on_downtime = move |evt: ev::Event| {
+        let Ok(downtime) = event_target_value(&evt).parse::<u8>() else {
+            return;
+        };
+        let state = match unit_state.get() {
+            Some(UnitState::Inactive { pin_stone, .. }) => UnitState::Inactive {
+                downtime,
+                pin_stone,
+            },
+            Some(UnitState::Pinned { .. }) => UnitState::Pinned { downtime },
+            Some(UnitState::Active) | None => return,
+        };
+        set_unit_state.run(state);
+    };
+    let on_pin_stone = move |evt: ev::Event| {
+        set_unit_state.run(UnitState::Inactive {
+            downtime: downtime(),
+            pin_stone: event_target_checked(&evt),
+        });
+    };
+
+    view! {
+        <Show when=move || unit_state.get().is_some()>
+            <div class="buttons">
+                <label>
+                    "State "
+                    <select prop:value=state_name on:change=on_state>
+                        <option value="active">Active</option>
+                        <option value="inactive">Inactive</option>
+                        <option value="pinned">Pinned</option>
+                    </select>
+                </label>
+                <Show when=move || state_name() != "active">
+                    <label>
+                        "Downtime (stones) "
+                        <input
+                            type="number"
+                            min="0"
+                            max="255"
+                            prop:value=downtime
+                            on:change=on_downtime
+                        />
+                    </label>
+                </Show>
+                <Show when=move || state_name() == "inactive">
+                    <label>
+                        "Pin stone "
+                        <input type="checkbox" prop:checked=pin_stone on:change=on_pin_stone />
+                    </label>
+                </Show>
+            </div>
+        </Show>
+    }
+}